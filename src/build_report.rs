@@ -0,0 +1,176 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+use crate::exit_codes::CliError;
+use crate::sources::ExcludeReason;
+
+/// Suffix of the report `build --keep-going` writes beside the cache, as
+/// `.{name}.build-report.json`. Inside the cache it would be an orphan file.
+pub const BUILD_REPORT_SUFFIX: &str = "build-report.json";
+
+/// Why a source file was not built, without failing the build.
+#[derive(Clone, Copy)]
+pub enum SkipReason {
+    Excluded(ExcludeReason),
+    IgnoredExtension,
+    Oversized,
+    NonUtf8,
+    Empty,
+}
+
+impl SkipReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Excluded(reason) => reason.as_str(),
+            Self::IgnoredExtension => "ignored_extension",
+            Self::Oversized => "oversized",
+            Self::NonUtf8 => "non_utf8",
+            Self::Empty => "empty",
+        }
+    }
+
+    /// Exclusions were asked for; every other skip is a warning.
+    pub fn is_warning(self) -> bool {
+        !matches!(self, Self::Excluded(_))
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum FailReason {
+    InvalidId,
+    Unreadable,
+    FrontMatter,
+    Ingest,
+}
+
+impl FailReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::InvalidId => "invalid_id",
+            Self::Unreadable => "unreadable",
+            Self::FrontMatter => "front_matter",
+            Self::Ingest => "ingest_error",
+        }
+    }
+}
+
+/// An error that stopped one source file from being built.
+pub struct Failure {
+    pub reason: FailReason,
+    pub detail: String,
+}
+
+impl Failure {
+    pub fn new(reason: FailReason, detail: impl fmt::Display) -> Self {
+        Failure {
+            reason,
+            detail: detail.to_string(),
+        }
+    }
+
+    /// The error a build without `--keep-going` stops with.
+    pub fn into_error(self, path: &Path) -> CliError {
+        let path = path.display();
+        let detail = self.detail;
+        match self.reason {
+            FailReason::InvalidId => CliError::internal(format!("ID error for {path}: {detail}")),
+            FailReason::Unreadable => CliError::io_error(format!("reading {path}: {detail}")),
+            FailReason::FrontMatter => {
+                CliError::internal(format!("front matter in {path}: {detail}"))
+            }
+            FailReason::Ingest => CliError::internal(format!("ingesting {path}: {detail}")),
+        }
+    }
+}
+
+/// What happened to every source file in a build, keyed by source path so
+/// the report does not depend on discovery order or the sources location.
+#[derive(Default)]
+pub struct BuildReport {
+    included: BTreeMap<String, Vec<String>>,
+    skipped: BTreeMap<String, SkipReason>,
+    failed: BTreeMap<String, Failure>,
+}
+
+impl BuildReport {
+    pub fn include(&mut self, source: &str, ids: Vec<String>) {
+        self.included.insert(source.to_string(), ids);
+    }
+
+    pub fn skip(&mut self, source: &str, reason: SkipReason) {
+        self.skipped.insert(source.to_string(), reason);
+    }
+
+    pub fn fail(&mut self, source: &str, failure: Failure) {
+        self.failed.insert(source.to_string(), failure);
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.skipped.len()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.failed.len()
+    }
+
+    pub fn warnings(&self) -> usize {
+        self.skipped.values().filter(|r| r.is_warning()).count()
+    }
+
+    /// The report as JSON. `cache_version` is `None` when the cache was not
+    /// written, and is then recorded as `null`.
+    pub fn to_json(&self, cache_version: Option<&str>) -> Value {
+        let included: Vec<Value> = self
+            .included
+            .iter()
+            .map(|(source, ids)| json!({ "source": source, "documents": ids }))
+            .collect();
+        let skipped: Vec<Value> = self
+            .skipped
+            .iter()
+            .map(|(source, reason)| {
+                json!({
+                    "source": source,
+                    "reason": reason.as_str(),
+                    "warning": reason.is_warning(),
+                })
+            })
+            .collect();
+        let failed: Vec<Value> = self
+            .failed
+            .iter()
+            .map(|(source, failure)| {
+                json!({
+                    "source": source,
+                    "reason": failure.reason.as_str(),
+                    "message": failure.detail,
+                })
+            })
+            .collect();
+
+        json!({
+            "cache_version": cache_version,
+            "counts": {
+                "included": included.len(),
+                "skipped": skipped.len(),
+                "failed": failed.len(),
+                "warnings": self.warnings(),
+            },
+            "included": included,
+            "skipped": skipped,
+            "failed": failed,
+        })
+    }
+
+    /// Write the report as pretty JSON to `path`.
+    pub fn write(&self, path: &Path, cache_version: Option<&str>) -> Result<(), CliError> {
+        let mut bytes = serde_json::to_vec_pretty(&self.to_json(cache_version))
+            .map_err(|e| CliError::internal(e))?;
+        bytes.push(b'\n');
+        std::fs::write(path, bytes)
+            .map_err(|e| CliError::io_error(format!("writing {}: {e}", path.display())))
+    }
+}
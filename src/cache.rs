@@ -0,0 +1,47 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use context_core::cache::{CacheManifest, ContextCache};
+use context_core::document::Document;
+
+use crate::exit_codes::{self, CliError};
+
+/// Load and parse `manifest.json` from a cache directory.
+pub fn load_manifest(cache_path: &Path) -> Result<CacheManifest, CliError> {
+    let manifest_path = cache_path.join("manifest.json");
+    let manifest_file = std::fs::File::open(&manifest_path)
+        .map_err(|e| exit_codes::from_io_error(e, cache_path))?;
+    serde_json::from_reader(manifest_file).map_err(exit_codes::from_manifest_parse)
+}
+
+/// The directory a cache path names right now. `build` swaps a cache by
+/// repointing its path at a new version, so a command that reads through
+/// the resolved directory sees one version throughout.
+pub fn resolve_root(cache_path: &Path) -> PathBuf {
+    std::fs::canonicalize(cache_path).unwrap_or_else(|_| cache_path.to_path_buf())
+}
+
+/// Load a cache directory for selection.
+pub fn load_cache(cache_path: &Path) -> Result<ContextCache, CliError> {
+    let root = resolve_root(cache_path);
+    let manifest = load_manifest(&root)?;
+    Ok(ContextCache { root, manifest })
+}
+
+/// Read a stored document file listed in the manifest as JSON.
+pub fn read_document(cache_path: &Path, file: &str) -> Result<Value, CliError> {
+    let bytes = std::fs::read(cache_path.join(file))
+        .map_err(|e| CliError::cache_invalid(format!("reading {file}: {e}")))?;
+    serde_json::from_slice(&bytes)
+        .map_err(|e| CliError::cache_invalid(format!("invalid document {file}: {e}")))
+}
+
+/// Token count of a stored document, as the selection engine counts it
+/// against the budget.
+pub fn document_tokens(cache_path: &Path, file: &str) -> Result<usize, CliError> {
+    let stored = read_document(cache_path, file)?;
+    let document: Document = serde_json::from_value(stored)
+        .map_err(|e| CliError::cache_invalid(format!("invalid document {file}: {e}")))?;
+    Ok(document.token_count())
+}
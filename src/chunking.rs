@@ -0,0 +1,177 @@
+use std::path::Path;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use context_core::document::{Document, DocumentId, Metadata};
+
+/// Strategy for splitting one source file into several documents.
+#[derive(ValueEnum, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ChunkBy {
+    /// One document per markdown heading section
+    Heading,
+}
+
+/// A section of a source document.
+pub struct Chunk {
+    /// Heading anchor, or `None` for content before the first heading.
+    pub anchor: Option<String>,
+    /// Titles of the enclosing headings, outermost first.
+    pub heading_path: Vec<String>,
+    pub content: String,
+}
+
+/// Token count of `text` as the selection engine counts it against the
+/// budget, used for `--max-chunk-tokens`. Text the engine cannot ingest
+/// counts as zero; ingesting the chunk itself reports the failure.
+pub fn count_tokens(text: &str) -> usize {
+    let metadata: Option<Metadata> = serde_json::from_value(Value::Object(Map::new())).ok();
+    let id = DocumentId::from_path(Path::new(""), Path::new("chunk")).ok();
+    let (Some(id), Some(metadata)) = (id, metadata) else {
+        return 0;
+    };
+    Document::ingest(id, String::new(), text.as_bytes().to_vec(), metadata)
+        .map_or(0, |doc| doc.token_count())
+}
+
+/// Split markdown at ATX headings outside code fences.
+///
+/// Anchors follow GitHub's slug rules, with `-1`, `-2`, ... appended to
+/// repeated slugs, so they are stable for a given heading sequence. Sections
+/// over `max_tokens` are further split at blank lines and their anchors get
+/// a `--N` suffix from the second part on.
+pub fn split_by_heading(text: &str, max_tokens: Option<usize>) -> Vec<Chunk> {
+    let mut sections: Vec<Chunk> = vec![Chunk {
+        anchor: None,
+        heading_path: Vec::new(),
+        content: String::new(),
+    }];
+    let mut stack: Vec<(usize, String)> = Vec::new();
+    let mut seen_slugs: Vec<String> = Vec::new();
+    let mut fence: Option<&str> = None;
+
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+        } else if trimmed.starts_with("```") {
+            fence = Some("```");
+        } else if trimmed.starts_with("~~~") {
+            fence = Some("~~~");
+        } else if let Some((level, title)) = parse_heading(line) {
+            while stack.last().is_some_and(|(l, _)| *l >= level) {
+                stack.pop();
+            }
+            stack.push((level, title.clone()));
+            sections.push(Chunk {
+                anchor: Some(unique_slug(&title, &mut seen_slugs)),
+                heading_path: stack.iter().map(|(_, t)| t.clone()).collect(),
+                content: String::new(),
+            });
+        }
+        if let Some(current) = sections.last_mut() {
+            current.content.push_str(line);
+        }
+    }
+
+    // Drop a preamble that is only whitespace
+    if sections[0].content.trim().is_empty() && sections.len() > 1 {
+        sections.remove(0);
+    }
+
+    match max_tokens {
+        Some(max) => sections
+            .into_iter()
+            .flat_map(|section| split_oversized(section, max))
+            .collect(),
+        None => sections,
+    }
+}
+
+/// Parse an ATX heading line into its level and title.
+fn parse_heading(line: &str) -> Option<(usize, String)> {
+    let line = line.trim_end();
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let rest = &line[indent..];
+    let level = rest.chars().take_while(|&c| c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let rest = &rest[level..];
+    if !rest.is_empty() && !rest.starts_with(|c: char| c == ' ' || c == '\t') {
+        return None;
+    }
+    let title = rest.trim().trim_end_matches('#').trim_end().to_string();
+    Some((level, title))
+}
+
+/// GitHub-style heading slug, de-duplicated against `seen`.
+fn unique_slug(title: &str, seen: &mut Vec<String>) -> String {
+    let mut base: String = title
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            '-' | '_' => Some(c),
+            c if c.is_alphanumeric() => Some(c),
+            _ => None,
+        })
+        .collect();
+    if base.is_empty() {
+        base = "section".to_string();
+    }
+
+    let mut slug = base.clone();
+    let mut n = 0;
+    while seen.contains(&slug) {
+        n += 1;
+        slug = format!("{base}-{n}");
+    }
+    seen.push(slug.clone());
+    slug
+}
+
+/// Split a section into parts of at most `max` tokens at paragraph
+/// boundaries. A single paragraph over the limit stays whole.
+///
+/// Each candidate part is counted whole rather than by summing its
+/// paragraphs, since the engine's count need not be additive.
+fn split_oversized(section: Chunk, max: usize) -> Vec<Chunk> {
+    if count_tokens(&section.content) <= max {
+        return vec![section];
+    }
+
+    let mut parts: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for paragraph in section.content.split_inclusive("\n\n") {
+        if !current.is_empty() && count_tokens(&format!("{current}{paragraph}")) > max {
+            parts.push(std::mem::take(&mut current));
+        }
+        current.push_str(paragraph);
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+
+    let base = section.anchor.clone().unwrap_or_else(|| "preamble".to_string());
+    parts
+        .into_iter()
+        .enumerate()
+        .map(|(i, content)| Chunk {
+            anchor: if i == 0 {
+                section.anchor.clone()
+            } else {
+                Some(format!("{base}--{}", i + 1))
+            },
+            heading_path: section.heading_path.clone(),
+            content,
+        })
+        .collect()
+}
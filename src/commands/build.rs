@@ -1,88 +1,794 @@
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
 
 use clap::Args;
-use walkdir::WalkDir;
+use fs2::FileExt;
+use notify::{Event, RecursiveMode, Watcher};
+use serde_json::{json, Map, Value};
 
 use context_core::cache::{CacheBuildConfig, CacheBuilder};
 use context_core::document::{Document, DocumentId, Metadata};
+use context_core::selection::ContextSelector;
 
-use crate::exit_codes::CliError;
+use crate::build_report::{
+    BuildReport, FailReason, Failure, SkipReason, BUILD_REPORT_SUFFIX,
+};
+use crate::cache;
+use crate::chunking::{self, ChunkBy};
+use crate::commands::diff_resolve;
+use crate::config::{self, Config};
+use crate::exit_codes::{CliError, USAGE_ERROR};
+use crate::formats::SourceFormat;
+use crate::frontmatter::{self, UnknownKeys};
+use crate::queries::{self, BatchQuery};
+use crate::sources::{self, ExcludeReason, SourceFile, SourceRules};
 
-#[derive(Args)]
+/// Quiet period after the last change before `--watch` rebuilds.
+const DEFAULT_DEBOUNCE_MS: u64 = 200;
+
+#[derive(Args, Default)]
 pub struct BuildArgs {
     /// Directory containing .md source files
     #[arg(long)]
-    pub sources: PathBuf,
+    pub sources: Option<PathBuf>,
 
     /// Output cache directory
     #[arg(long)]
-    pub cache: PathBuf,
+    pub cache: Option<PathBuf>,
 
     /// Remove existing cache before building
     #[arg(long)]
     pub force: bool,
+
+    /// Reuse unchanged documents from the existing cache and replace it
+    #[arg(long, conflicts_with = "force")]
+    pub incremental: bool,
+
+    /// How to treat front-matter keys other than title, tags, owner, audience, version
+    #[arg(long, value_enum)]
+    pub unknown_metadata: Option<UnknownKeys>,
+
+    /// Only build sources matching this glob, relative to --sources (repeatable)
+    #[arg(long = "include", value_name = "GLOB")]
+    pub include: Vec<String>,
+
+    /// Skip sources matching this glob, relative to --sources (repeatable)
+    #[arg(long = "exclude", value_name = "GLOB")]
+    pub exclude: Vec<String>,
+
+    /// Source formats to ingest, by extension (comma-separated) [default: md]
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub formats: Vec<SourceFormat>,
+
+    /// Split each source into one document per section
+    #[arg(long, value_enum)]
+    pub chunk_by: Option<ChunkBy>,
+
+    /// Further split sections above this many tokens, as resolve counts them
+    #[arg(long)]
+    pub max_chunk_tokens: Option<usize>,
+
+    /// Keep running and rebuild whenever a source changes
+    #[arg(long)]
+    pub watch: bool,
+
+    /// Milliseconds without changes to wait before rebuilding [default: 200]
+    #[arg(long, value_name = "MS")]
+    pub debounce: Option<u64>,
+
+    /// JSON-lines file of {"query", "budget", "id"} objects to re-run after each rebuild
+    #[arg(long, value_name = "FILE")]
+    pub queries: Option<PathBuf>,
+
+    /// Record sources that fail to build and continue instead of stopping
+    #[arg(long)]
+    pub keep_going: bool,
+
+    /// Write the build report to this path
+    #[arg(long, value_name = "PATH")]
+    pub report: Option<PathBuf>,
+
+    /// Skip sources larger than this many bytes
+    #[arg(long, value_name = "BYTES")]
+    pub max_file_bytes: Option<u64>,
+
+    /// Exit with code 1 if more than this many sources fail to build
+    #[arg(long, value_name = "N")]
+    pub max_failed: Option<usize>,
+
+    /// Exit with code 1 if more than this many sources are skipped with a warning
+    #[arg(long, value_name = "N")]
+    pub max_warnings: Option<usize>,
+}
+
+impl BuildArgs {
+    /// Fill options not given on the command line from `context.toml`, then
+    /// apply built-in defaults.
+    pub fn with_config(mut self, config: &Config) -> Self {
+        let build = &config.build;
+        self.sources = self.sources.or_else(|| build.sources.clone());
+        self.cache = self.cache.or_else(|| config.build_cache());
+        if !self.force && !self.incremental {
+            self.force = build.force.unwrap_or(false);
+            self.incremental = build.incremental.unwrap_or(false);
+        }
+        self.unknown_metadata = self
+            .unknown_metadata
+            .or(build.unknown_metadata)
+            .or(Some(UnknownKeys::default()));
+        if self.include.is_empty() {
+            self.include = build.include.clone().unwrap_or_default();
+        }
+        if self.exclude.is_empty() {
+            self.exclude = build.exclude.clone().unwrap_or_default();
+        }
+        if self.formats.is_empty() {
+            self.formats = build
+                .formats
+                .clone()
+                .unwrap_or_else(|| vec![SourceFormat::Md]);
+        }
+        self.chunk_by = self.chunk_by.or(build.chunk_by);
+        self.max_chunk_tokens = self.max_chunk_tokens.or(build.max_chunk_tokens);
+        self.debounce = self.debounce.or(build.debounce);
+        self.queries = self.queries.or_else(|| build.queries.clone());
+        self.keep_going = self.keep_going || build.keep_going.unwrap_or(false);
+        self.report = self.report.or_else(|| build.report.clone());
+        self.max_file_bytes = self.max_file_bytes.or(build.max_file_bytes);
+        self.max_failed = self.max_failed.or(build.max_failed);
+        self.max_warnings = self.max_warnings.or(build.max_warnings);
+        self
+    }
+}
+
+/// Documents from a previously built cache, keyed by document ID.
+struct PreviousCache {
+    root: PathBuf,
+    files: BTreeMap<String, String>,
+}
+
+impl PreviousCache {
+    fn load(cache_path: &Path) -> Result<Self, CliError> {
+        let root = cache::resolve_root(cache_path);
+        let manifest = cache::load_manifest(&root)?;
+        let files = manifest
+            .documents
+            .iter()
+            .map(|entry| (entry.id.to_string(), entry.file.clone()))
+            .collect();
+        Ok(Self { root, files })
+    }
+
+    /// Return the stored document for `id` if its source path, content, and
+    /// metadata are unchanged. Anything that does not match exactly is
+    /// re-ingested.
+    fn reuse(
+        &self,
+        id: &DocumentId,
+        source: &str,
+        raw_content: &[u8],
+        metadata: &Metadata,
+    ) -> Option<Document> {
+        let file = self.files.get(&id.to_string())?;
+        let stored = std::fs::read(self.root.join(file)).ok()?;
+        let value: serde_json::Value = serde_json::from_slice(&stored).ok()?;
+        if value.get("source").and_then(|s| s.as_str()) != Some(source) {
+            return None;
+        }
+        if value.get("content").and_then(|c| c.as_str()).map(str::as_bytes) != Some(raw_content)
+        {
+            return None;
+        }
+        if value.get("metadata") != serde_json::to_value(metadata).ok().as_ref() {
+            return None;
+        }
+        serde_json::from_value(value).ok()
+    }
+}
+
+/// Split one source file into per-section documents.
+///
+/// Each chunk's ID is derived by `DocumentId::from_path`, like the file's,
+/// from the source path with `#` and the heading anchor appended; content
+/// before the first heading keeps the file's ID. The parent source path and
+/// heading path are added to the chunk's metadata.
+fn chunk_pieces(
+    sources_root: &Path,
+    path: &Path,
+    file_id: &DocumentId,
+    source: &str,
+    text: &str,
+    fields: &Map<String, Value>,
+    max_tokens: Option<usize>,
+) -> Result<Vec<(DocumentId, Vec<u8>, Map<String, Value>)>, String> {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    chunking::split_by_heading(text, max_tokens)
+        .into_iter()
+        .map(|chunk| {
+            let id = match &chunk.anchor {
+                Some(anchor) => {
+                    let chunk_path = path.with_file_name(format!("{file_name}#{anchor}"));
+                    DocumentId::from_path(sources_root, &chunk_path).map_err(|e| e.to_string())?
+                }
+                None => file_id.clone(),
+            };
+            let mut fields = fields.clone();
+            fields.insert("parent".to_string(), Value::String(source.to_string()));
+            fields.insert(
+                "heading_path".to_string(),
+                Value::Array(chunk.heading_path.into_iter().map(Value::String).collect()),
+            );
+            Ok((id, chunk.content.into_bytes(), fields))
+        })
+        .collect()
+}
+
+/// Hidden sibling of the cache directory, `.{name}.{suffix}`.
+fn sibling_path(cache_path: &Path, suffix: &str) -> PathBuf {
+    let name = cache_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "cache".to_string());
+    cache_path.with_file_name(format!(".{name}.{suffix}"))
+}
+
+/// Sibling directory used to assemble a replacement cache.
+fn staging_path(cache_path: &Path) -> PathBuf {
+    sibling_path(cache_path, "staging")
+}
+
+/// Take an exclusive lock on `cache_path` for the duration of a build. The
+/// lock is released when the returned file is dropped, including when the
+/// process dies.
+fn lock_cache(cache_path: &Path) -> Result<File, CliError> {
+    let path = sibling_path(cache_path, "lock");
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(|e| CliError::io_error(&e))?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .map_err(|e| CliError::io_error(format!("{}: {e}", path.display())))?;
+    file.try_lock_exclusive().map_err(|e| {
+        if e.kind() == fs2::lock_contended_error().kind() {
+            CliError::io_error(format!(
+                "another build is already writing {}",
+                cache_path.display()
+            ))
+        } else {
+            CliError::io_error(format!("locking {}: {e}", path.display()))
+        }
+    })?;
+    Ok(file)
+}
+
+/// Move the finished `staging` cache to `cache_path`.
+///
+/// On Unix, `cache_path` is a symlink to a versioned sibling directory,
+/// `.{name}.v-{cache_version}`. The new version is renamed next to the old
+/// one and the link is replaced with one atomic rename, so `cache_path`
+/// always names a complete cache. Readers resolve the link once (see
+/// `cache::resolve_root`), and the version they resolved is kept until the
+/// next swap, so a reader partway through it can finish. A cache directory
+/// from before links were used is moved aside to `.{name}.old` on its first
+/// rebuild, which is the only time `cache_path` is briefly missing.
+#[cfg(unix)]
+fn swap_into_place(
+    staging: &Path,
+    cache_path: &Path,
+    cache_version: &str,
+) -> Result<(), CliError> {
+    let io_error = |e: std::io::Error| CliError::io_error(&e);
+    let version: String = cache_version
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let target = sibling_path(cache_path, &format!("v-{version}"));
+    let target_name = target.file_name().expect("sibling paths have a name").to_owned();
+
+    // Versioned directories are only ever renamed into place complete, so an
+    // existing one with this version is already the cache we just built
+    if target.exists() {
+        std::fs::remove_dir_all(staging).map_err(io_error)?;
+    } else {
+        std::fs::rename(staging, &target).map_err(io_error)?;
+    }
+
+    let previous = match std::fs::symlink_metadata(cache_path) {
+        Ok(meta) if meta.file_type().is_symlink() => std::fs::read_link(cache_path)
+            .map_err(io_error)?
+            .file_name()
+            .map(|name| name.to_owned()),
+        Ok(_) => {
+            let old = sibling_path(cache_path, "old");
+            if old.exists() {
+                std::fs::remove_dir_all(&old).map_err(io_error)?;
+            }
+            std::fs::rename(cache_path, &old).map_err(io_error)?;
+            old.file_name().map(|name| name.to_owned())
+        }
+        Err(_) => None,
+    };
+
+    if previous.as_deref() != Some(target_name.as_os_str()) {
+        let link = sibling_path(cache_path, "link");
+        if std::fs::symlink_metadata(&link).is_ok() {
+            std::fs::remove_file(&link).map_err(io_error)?;
+        }
+        std::os::unix::fs::symlink(&target_name, &link).map_err(io_error)?;
+        std::fs::rename(&link, cache_path).map_err(io_error)?;
+    }
+
+    // Keep the new version and the one readers may still be on
+    let name = cache_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "cache".to_string());
+    let version_prefix = format!(".{name}.v-");
+    let old_name = format!(".{name}.old");
+    let parent = target
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    for entry in std::fs::read_dir(parent).map_err(io_error)?.flatten() {
+        let file_name = entry.file_name();
+        let entry_name = file_name.to_string_lossy();
+        let stale = (entry_name.starts_with(&version_prefix) || entry_name == old_name)
+            && file_name != target_name
+            && previous.as_deref() != Some(file_name.as_os_str());
+        if stale {
+            let _ = std::fs::remove_dir_all(entry.path());
+        }
+    }
+    Ok(())
+}
+
+/// Move the finished `staging` cache to `cache_path`. An existing cache is
+/// renamed aside and removed only once the new one is in place, so every
+/// failure leaves a complete cache behind. Without symlinks this is two
+/// renames: `cache_path` is briefly missing between them.
+#[cfg(not(unix))]
+fn swap_into_place(
+    staging: &Path,
+    cache_path: &Path,
+    _cache_version: &str,
+) -> Result<(), CliError> {
+    if !cache_path.exists() {
+        return std::fs::rename(staging, cache_path).map_err(|e| CliError::io_error(&e));
+    }
+
+    let old = sibling_path(cache_path, "old");
+    if old.exists() {
+        std::fs::remove_dir_all(&old).map_err(|e| CliError::io_error(&e))?;
+    }
+    std::fs::rename(cache_path, &old).map_err(|e| CliError::io_error(&e))?;
+    if let Err(e) = std::fs::rename(staging, cache_path) {
+        let _ = std::fs::rename(&old, cache_path);
+        return Err(CliError::io_error(&e));
+    }
+    std::fs::remove_dir_all(&old).map_err(|e| CliError::io_error(&e))
+}
+
+/// Check option combinations once `context.toml` is merged, so a value from
+/// the file satisfies them just like a flag.
+fn validate(args: &BuildArgs) -> Result<(), CliError> {
+    if args.max_chunk_tokens.is_some() && args.chunk_by.is_none() {
+        return Err(CliError::new(USAGE_ERROR, "--max-chunk-tokens requires --chunk-by"));
+    }
+    if !args.watch && (args.debounce.is_some() || args.queries.is_some()) {
+        return Err(CliError::new(USAGE_ERROR, "--debounce and --queries require --watch"));
+    }
+    Ok(())
 }
 
 pub fn run(args: BuildArgs) -> Result<(), CliError> {
+    validate(&args)?;
+    if args.watch {
+        return watch(args);
+    }
+    build(&args)
+}
+
+fn build(args: &BuildArgs) -> Result<(), CliError> {
+    let sources_root = config::required(args.sources.clone(), "--sources")?;
+    let cache_path = config::required(args.cache.clone(), "--cache")?;
+
     // Validate sources directory exists
-    if !args.sources.is_dir() {
+    if !sources_root.is_dir() {
         return Err(CliError::io_error(format!(
             "sources directory does not exist: {}",
-            args.sources.display()
+            sources_root.display()
         )));
     }
 
-    // Handle --force
-    if args.force && args.cache.exists() {
-        std::fs::remove_dir_all(&args.cache).map_err(|e| CliError::io_error(&e))?;
+    // Only one build may write to a cache path at a time
+    let _lock = lock_cache(&cache_path)?;
+
+    // An existing cache is replaced only with --force or --incremental
+    if cache_path.exists() && !args.force && !args.incremental {
+        return Err(CliError::io_error(format!(
+            "cache already exists: {} (pass --force to replace it)",
+            cache_path.display()
+        )));
     }
 
-    // Walk sources for .md files
-    let mut documents = Vec::new();
-    for entry in WalkDir::new(&args.sources)
-        .sort_by_file_name()
-        .into_iter()
-        .filter_map(|e| e.ok())
-    {
-        let path = entry.path();
-        if !path.is_file() {
+    // Handle --incremental (a missing cache falls back to a full build)
+    let previous = if args.incremental && cache_path.exists() {
+        Some(PreviousCache::load(&cache_path)?)
+    } else {
+        None
+    };
+
+    // Walk sources, applying .contextignore and --include/--exclude
+    let rules = SourceRules::new(&args.include, &args.exclude)?;
+    let discovery = sources::discover(&sources_root, &rules)?;
+    let mut report = BuildReport::default();
+    for excluded in &discovery.excluded {
+        // Files outside --include that would not be ingested anyway are
+        // reported by extension without a stderr line each
+        if matches!(excluded.reason, ExcludeReason::NotIncluded)
+            && source_format(Path::new(&excluded.source), &args.formats).is_none()
+        {
+            report.skip(&excluded.source, SkipReason::IgnoredExtension);
             continue;
         }
-        let ext = path.extension().and_then(|e| e.to_str());
-        if ext != Some("md") {
+        eprintln!("Excluded {} ({})", excluded.source, excluded.reason.as_str());
+        report.skip(&excluded.source, SkipReason::Excluded(excluded.reason));
+    }
+
+    // Ingest files in the selected formats
+    let mut documents = Vec::new();
+    let mut reused = 0usize;
+    for file in discovery.files {
+        let Some(format) = source_format(&file.path, &args.formats) else {
+            report.skip(&file.source, SkipReason::IgnoredExtension);
             continue;
+        };
+
+        // Handle --keep-going
+        match ingest_source(args, &sources_root, &file, format, previous.as_ref()) {
+            Ok(Ingested::Built {
+                documents: built,
+                ids,
+                reused: reused_here,
+            }) => {
+                report.include(&file.source, ids);
+                documents.extend(built);
+                reused += reused_here;
+            }
+            Ok(Ingested::Skipped(reason)) => {
+                eprintln!("Skipped {} ({})", file.source, reason.as_str());
+                report.skip(&file.source, reason);
+            }
+            Err(failure) if args.keep_going => {
+                eprintln!(
+                    "Failed {} ({}): {}",
+                    file.source,
+                    failure.reason.as_str(),
+                    failure.detail
+                );
+                report.fail(&file.source, failure);
+            }
+            Err(failure) => return Err(failure.into_error(&file.path)),
         }
+    }
 
-        let id = DocumentId::from_path(&args.sources, path)
-            .map_err(|e| CliError::internal(format!("ID error for {}: {e}", path.display())))?;
+    let doc_count = documents.len();
 
-        let source = path
-            .strip_prefix(&args.sources)
-            .unwrap_or(path)
-            .to_string_lossy()
-            .to_string();
+    // Build next to the cache path, then swap it into place
+    let builder = CacheBuilder::new(CacheBuildConfig::v0());
+    let staging = staging_path(&cache_path);
+    if staging.exists() {
+        std::fs::remove_dir_all(&staging).map_err(|e| CliError::io_error(&e))?;
+    }
+    let cache = match builder.build(documents, &staging) {
+        Ok(cache) => cache,
+        Err(e) => {
+            let _ = std::fs::remove_dir_all(&staging);
+            return Err(e.into());
+        }
+    };
+    let cache_version = cache.manifest.cache_version.to_string();
 
-        let raw_content = std::fs::read(path).map_err(|e| {
-            CliError::io_error(format!("reading {}: {e}", path.display()))
-        })?;
+    // Handle --max-failed and --max-warnings. The report is still written,
+    // with no cache version, and any existing cache is left in place
+    let thresholds = check_thresholds(args, &report);
+    let written_version = thresholds.is_ok().then_some(cache_version.as_str());
 
-        let doc = Document::ingest(id, source, raw_content, Metadata::default()).map_err(
-            |e| CliError::internal(format!("ingesting {}: {e}", path.display())),
-        )?;
+    // Handle --report (with --keep-going alone, the report goes beside the cache)
+    match &args.report {
+        Some(path) => report.write(path, written_version)?,
+        None if args.keep_going => {
+            report.write(&sibling_path(&cache_path, BUILD_REPORT_SUFFIX), written_version)?
+        }
+        None => {}
+    }
+    if args.keep_going {
+        eprintln!(
+            "Build report: {} skipped, {} failed",
+            report.skipped(),
+            report.failed()
+        );
+    }
+
+    if let Err(e) = thresholds {
+        let _ = std::fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+    swap_into_place(&staging, &cache_path, &cache_version)?;
+
+    if args.incremental {
+        eprintln!(
+            "Built cache: {} documents ({} reused), version {}",
+            doc_count, reused, cache_version
+        );
+    } else {
+        eprintln!(
+            "Built cache: {} documents, version {}",
+            doc_count, cache_version
+        );
+    }
+
+    Ok(())
+}
+
+/// Fail with `threshold_failed` if the report exceeds `--max-failed` or
+/// `--max-warnings`.
+fn check_thresholds(args: &BuildArgs, report: &BuildReport) -> Result<(), CliError> {
+    if let Some(max) = args.max_failed.filter(|&max| report.failed() > max) {
+        return Err(CliError::threshold_failed(format!(
+            "{} sources failed, above --max-failed {max}",
+            report.failed()
+        )));
+    }
+    if let Some(max) = args.max_warnings.filter(|&max| report.warnings() > max) {
+        return Err(CliError::threshold_failed(format!(
+            "{} sources skipped with warnings, above --max-warnings {max}",
+            report.warnings()
+        )));
+    }
+    Ok(())
+}
+
+/// Outcome of building one source file.
+enum Ingested {
+    Built {
+        documents: Vec<Document>,
+        ids: Vec<String>,
+        reused: usize,
+    },
+    Skipped(SkipReason),
+}
+
+/// Format of `path` by its extension, if it is one of `formats`.
+fn source_format(path: &Path, formats: &[SourceFormat]) -> Option<SourceFormat> {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .and_then(SourceFormat::from_extension)
+        .filter(|f| formats.contains(f))
+}
 
+/// Turn one source file into cache documents, reusing unchanged documents
+/// from `previous`.
+///
+/// With `--keep-going`, non-UTF-8 files and files with no content after
+/// front matter are skipped rather than ingested; `--max-file-bytes` skips
+/// larger files in any build.
+fn ingest_source(
+    args: &BuildArgs,
+    sources_root: &Path,
+    file: &SourceFile,
+    format: SourceFormat,
+    previous: Option<&PreviousCache>,
+) -> Result<Ingested, Failure> {
+    let path = file.path.as_path();
+    let source = &file.source;
+
+    let id = DocumentId::from_path(sources_root, path)
+        .map_err(|e| Failure::new(FailReason::InvalidId, e))?;
+
+    // Checked before reading, so an oversized file is never loaded
+    if let Some(max) = args.max_file_bytes {
+        let len = std::fs::metadata(path)
+            .map_err(|e| Failure::new(FailReason::Unreadable, e))?
+            .len();
+        if len > max {
+            return Ok(Ingested::Skipped(SkipReason::Oversized));
+        }
+    }
+    let raw_content = std::fs::read(path).map_err(|e| Failure::new(FailReason::Unreadable, e))?;
+    if args.keep_going && std::str::from_utf8(&raw_content).is_err() {
+        return Ok(Ingested::Skipped(SkipReason::NonUtf8));
+    }
+
+    let unknown_metadata = args.unknown_metadata.unwrap_or_default();
+    let front_matter = frontmatter::split(raw_content, unknown_metadata)
+        .map_err(|e| Failure::new(FailReason::FrontMatter, e))?;
+    let raw_content = format.normalize(front_matter.body);
+    if args.keep_going && raw_content.iter().all(u8::is_ascii_whitespace) {
+        return Ok(Ingested::Skipped(SkipReason::Empty));
+    }
+
+    // One document per file, or one per section with --chunk-by
+    let pieces = match args.chunk_by {
+        None => vec![(id, raw_content, front_matter.fields)],
+        Some(ChunkBy::Heading) => match String::from_utf8(raw_content) {
+            Ok(text) => chunk_pieces(
+                sources_root,
+                path,
+                &id,
+                source,
+                &text,
+                &front_matter.fields,
+                args.max_chunk_tokens,
+            )
+            .map_err(|e| Failure::new(FailReason::InvalidId, e))?,
+            Err(e) => vec![(id, e.into_bytes(), front_matter.fields)],
+        },
+    };
+
+    let mut documents = Vec::with_capacity(pieces.len());
+    let mut ids = Vec::with_capacity(pieces.len());
+    let mut reused = 0usize;
+    for (id, raw_content, fields) in pieces {
+        let metadata: Metadata = serde_json::from_value(Value::Object(fields))
+            .map_err(|e| Failure::new(FailReason::FrontMatter, e))?;
+        ids.push(id.to_string());
+
+        if let Some(doc) = previous.and_then(|p| p.reuse(&id, source, &raw_content, &metadata)) {
+            reused += 1;
+            documents.push(doc);
+            continue;
+        }
+
+        let doc = Document::ingest(id, source.clone(), raw_content, metadata)
+            .map_err(|e| Failure::new(FailReason::Ingest, e))?;
         documents.push(doc);
     }
 
-    let doc_count = documents.len();
+    Ok(Ingested::Built {
+        documents,
+        ids,
+        reused,
+    })
+}
 
-    // Build cache
-    let builder = CacheBuilder::new(CacheBuildConfig::v0());
-    let cache = builder.build(documents, &args.cache)?;
+/// Rebuild whenever a file under `--sources` changes, until interrupted.
+///
+/// Changes are debounced. Every build, the first included, reuses an
+/// existing cache as with `--incremental` (unless `--force` asks for a full
+/// first build) and is swapped into place atomically, so readers never see
+/// a partially written cache. A failed build is reported and the previous
+/// cache stays in place. With
+/// `--queries`, the saved queries are re-run after each rebuild and one JSON
+/// line is printed listing the queries whose selection changed, in the
+/// `diff-resolve` report format.
+fn watch(args: BuildArgs) -> Result<(), CliError> {
+    let sources_root = config::required(args.sources.clone(), "--sources")?;
+    let cache_path = config::required(args.cache.clone(), "--cache")?;
+    let debounce = Duration::from_millis(args.debounce.unwrap_or(DEFAULT_DEBOUNCE_MS));
+    let saved = match &args.queries {
+        Some(path) => queries::read_query_file(path, None)?,
+        None => Vec::new(),
+    };
+
+    // Our own writes must not trigger another rebuild: the cache link, its
+    // hidden `.{name}.*` siblings (staging, versions, lock, and report), and
+    // --report
+    let cwd = std::env::current_dir().map_err(|e| CliError::io_error(&e))?;
+    let cache_link = cwd.join(&cache_path);
+    let siblings = cwd.join(sibling_path(&cache_path, "")).to_string_lossy().into_owned();
+    let report = args.report.as_ref().map(|report| cwd.join(report));
+    let ignored = |path: &Path| {
+        path.starts_with(&cache_link)
+            || path.to_string_lossy().starts_with(&siblings)
+            || report.as_ref().is_some_and(|report| path == report)
+    };
+
+    let watch_error =
+        |e: notify::Error| CliError::io_error(format!("watching {}: {e}", sources_root.display()));
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).map_err(watch_error)?;
+    watcher
+        .watch(&cwd.join(&sources_root), RecursiveMode::Recursive)
+        .map_err(watch_error)?;
 
-    eprintln!(
-        "Built cache: {} documents, version {}",
-        doc_count, cache.manifest.cache_version
-    );
+    // An existing cache is the normal starting point, so the first build is
+    // incremental too
+    let mut rebuild = BuildArgs {
+        incremental: !args.force,
+        watch: false,
+        ..args
+    };
+    if let Err(e) = build(&rebuild) {
+        eprintln!("error: {}", e.message);
+    }
+    let mut previous = select_saved(&cache_path, &saved);
+
+    rebuild.force = false;
+    rebuild.incremental = true;
+    eprintln!("Watching {} for changes", sources_root.display());
+    while wait_for_change(&rx, &ignored, debounce) {
+        if let Err(e) = build(&rebuild) {
+            eprintln!("error: {}", e.message);
+            continue;
+        }
+        if saved.is_empty() {
+            continue;
+        }
+
+        let current = select_saved(&cache_path, &saved);
+        if let (Some(old), Some(new)) = (&previous, &current) {
+            let changed: Vec<Value> = saved
+                .iter()
+                .zip(old.iter().zip(new))
+                .map(|(entry, (old, new))| diff_resolve::compare(entry, old, new))
+                .filter(|report| report["unchanged"] == false)
+                .collect();
+            let output = json!({
+                "changed_queries": changed.len(),
+                "queries": changed,
+            });
+
+            let stdout = std::io::stdout();
+            let mut out = stdout.lock();
+            serde_json::to_writer(&mut out, &output).map_err(|e| CliError::internal(e))?;
+            writeln!(out).map_err(|e| CliError::io_error(e))?;
+            out.flush().map_err(|e| CliError::io_error(e))?;
+        }
+        previous = current;
+    }
 
     Ok(())
 }
+
+/// Block until a change to a path not `ignored` arrives, then absorb further
+/// events until none arrive for `debounce`. Returns false once the watcher
+/// has stopped.
+fn wait_for_change(
+    rx: &Receiver<notify::Result<Event>>,
+    ignored: &dyn Fn(&Path) -> bool,
+    debounce: Duration,
+) -> bool {
+    let relevant = |event: &notify::Result<Event>| match event {
+        Ok(event) => !event.kind.is_access() && event.paths.iter().any(|path| !ignored(path)),
+        Err(e) => {
+            eprintln!("watch error: {e}");
+            false
+        }
+    };
+    loop {
+        match rx.recv() {
+            Ok(event) if relevant(&event) => break,
+            Ok(_) => continue,
+            Err(_) => return false,
+        }
+    }
+    while rx.recv_timeout(debounce).is_ok() {}
+    true
+}
+
+/// Selections for the saved queries against the cache as it is now, or
+/// `None` (after reporting why) if any of them fails.
+fn select_saved(cache_path: &Path, saved: &[BatchQuery]) -> Option<Vec<Value>> {
+    if saved.is_empty() {
+        return None;
+    }
+    let selector = ContextSelector::default();
+    let results = cache::load_cache(cache_path).and_then(|cache| {
+        saved
+            .iter()
+            .map(|entry| diff_resolve::select(&selector, &cache, entry))
+            .collect::<Result<Vec<_>, _>>()
+    });
+    results.map_err(|e| eprintln!("error: {}", e.message)).ok()
+}
@@ -0,0 +1,84 @@
+use std::io::Write;
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::cache;
+use crate::config::{self, Config};
+use crate::exit_codes::CliError;
+
+#[derive(Args, Default)]
+pub struct CatArgs {
+    /// Path to a built cache directory
+    #[arg(long)]
+    pub cache: Option<PathBuf>,
+
+    /// Document ID, or source path relative to the build's --sources
+    pub document: String,
+
+    /// Print the full stored document JSON instead of its content
+    #[arg(long)]
+    pub raw: bool,
+}
+
+impl CatArgs {
+    /// Fill options not given on the command line from `context.toml`.
+    pub fn with_config(mut self, config: &Config) -> Self {
+        self.cache = self.cache.or_else(|| config.inspect_cache());
+        self
+    }
+}
+
+pub fn run(args: CatArgs) -> Result<(), CliError> {
+    let cache_path = cache::resolve_root(&config::required(args.cache, "--cache")?);
+
+    // Load manifest
+    let manifest = cache::load_manifest(&cache_path)?;
+
+    // Match by ID first, then by source path (which may name several chunks)
+    let by_id: Vec<_> = manifest
+        .documents
+        .iter()
+        .filter(|entry| entry.id.to_string() == args.document)
+        .collect();
+
+    let mut matches = Vec::new();
+    if by_id.is_empty() {
+        let wanted = args.document.replace('\\', "/");
+        for entry in &manifest.documents {
+            let stored = cache::read_document(&cache_path, &entry.file)?;
+            let source = stored["source"].as_str().map(|s| s.replace('\\', "/"));
+            if source.as_deref() == Some(wanted.as_str()) {
+                matches.push(stored);
+            }
+        }
+    } else {
+        for entry in by_id {
+            matches.push(cache::read_document(&cache_path, &entry.file)?);
+        }
+    }
+
+    if matches.is_empty() {
+        return Err(CliError::document_not_found(&args.document));
+    }
+
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    for stored in &matches {
+        if args.raw {
+            serde_json::to_writer(&mut out, stored).map_err(|e| CliError::internal(e))?;
+            writeln!(out).map_err(|e| CliError::io_error(e))?;
+        } else {
+            let content = stored["content"]
+                .as_str()
+                .ok_or_else(|| CliError::cache_invalid("stored document has no content"))?;
+            out.write_all(content.as_bytes()).map_err(|e| CliError::io_error(e))?;
+            if !content.ends_with('\n') {
+                writeln!(out).map_err(|e| CliError::io_error(e))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
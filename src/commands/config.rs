@@ -0,0 +1,80 @@
+use std::io::Write;
+
+use clap::Subcommand;
+use serde_json::json;
+
+use crate::commands::build::BuildArgs;
+use crate::commands::inspect::{InspectArgs, ListingOptions};
+use crate::commands::resolve::ResolveArgs;
+use crate::config::LoadedConfig;
+use crate::exit_codes::CliError;
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// Print the effective configuration (context.toml plus defaults) as JSON
+    Show,
+}
+
+pub fn run(command: ConfigCommand, loaded: &LoadedConfig) -> Result<(), CliError> {
+    match command {
+        ConfigCommand::Show => show(loaded),
+    }
+}
+
+fn show(loaded: &LoadedConfig) -> Result<(), CliError> {
+    let config = &loaded.config;
+    let build = BuildArgs::default().with_config(config);
+    let resolve = ResolveArgs::default().with_config(config);
+    let inspect = InspectArgs::default().with_config(config);
+    let listing = ListingOptions::default().with_config(config);
+
+    let output = json!({
+        "config_path": loaded.path,
+        "build": {
+            "sources": build.sources,
+            "cache": build.cache,
+            "force": build.force,
+            "incremental": build.incremental,
+            "unknown_metadata": build.unknown_metadata,
+            "include": build.include,
+            "exclude": build.exclude,
+            "formats": build.formats,
+            "chunk_by": build.chunk_by,
+            "max_chunk_tokens": build.max_chunk_tokens,
+            "debounce": build.debounce,
+            "queries": build.queries,
+            "keep_going": build.keep_going,
+            "report": build.report,
+            "max_file_bytes": build.max_file_bytes,
+            "max_failed": build.max_failed,
+            "max_warnings": build.max_warnings,
+        },
+        "resolve": {
+            "cache": resolve.cache.first().map(|spec| &spec.path),
+            "query": resolve.query,
+            "budget": resolve.budget,
+            "format": resolve.format,
+            "filters": resolve.candidates.filters,
+            "path_prefixes": resolve.candidates.path_prefixes,
+            "exclude_ids": resolve.candidates.exclude_ids,
+            "pins": resolve.candidates.pins,
+            "header": resolve.header,
+            "separator": resolve.separator,
+            "template": resolve.template,
+        },
+        "inspect": {
+            "cache": inspect.cache,
+            "deep": inspect.deep,
+            "sort": listing.sort,
+            "prefix": listing.prefix,
+        },
+    });
+
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    serde_json::to_writer_pretty(&mut out, &output)
+        .map_err(|e| CliError::internal(e))?;
+    writeln!(out).map_err(|e| CliError::io_error(e))?;
+
+    Ok(())
+}
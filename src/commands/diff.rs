@@ -0,0 +1,145 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use serde_json::{json, Value};
+use similar::TextDiff;
+
+use context_core::cache::CacheManifest;
+
+use crate::cache;
+use crate::exit_codes::CliError;
+
+#[derive(Args)]
+pub struct DiffArgs {
+    /// Baseline cache directory
+    pub old: PathBuf,
+
+    /// Cache directory to compare against the baseline
+    pub new: PathBuf,
+
+    /// Include a unified diff of each modified document's content
+    #[arg(long)]
+    pub content: bool,
+
+    /// Exit with code 1 when the caches differ
+    #[arg(long)]
+    pub exit_code: bool,
+}
+
+/// Manifest entry fields compared between caches.
+struct Entry {
+    file: String,
+    version: String,
+}
+
+fn entries(manifest: &CacheManifest) -> BTreeMap<String, Entry> {
+    manifest
+        .documents
+        .iter()
+        .map(|entry| {
+            let fields = Entry {
+                file: entry.file.clone(),
+                version: entry.version.to_string(),
+            };
+            (entry.id.to_string(), fields)
+        })
+        .collect()
+}
+
+pub fn run(args: DiffArgs) -> Result<(), CliError> {
+    let old_root = cache::resolve_root(&args.old);
+    let new_root = cache::resolve_root(&args.new);
+
+    // Load manifests
+    let old_manifest = cache::load_manifest(&old_root)?;
+    let new_manifest = cache::load_manifest(&new_root)?;
+    let old_entries = entries(&old_manifest);
+    let new_entries = entries(&new_manifest);
+
+    let mut added = Vec::new();
+    let mut removed = Vec::new();
+    let mut modified = Vec::new();
+
+    for (id, old) in &old_entries {
+        match new_entries.get(id) {
+            None => removed.push(json!({ "id": id, "version": old.version })),
+            Some(new) if new.version != old.version => {
+                let mut change = json!({
+                    "id": id,
+                    "old_version": old.version,
+                    "new_version": new.version,
+                });
+                if args.content {
+                    change["diff"] = json!(content_diff(&old_root, old, &new_root, new, id)?);
+                }
+                modified.push(change);
+            }
+            Some(_) => {}
+        }
+    }
+    for (id, new) in &new_entries {
+        if !old_entries.contains_key(id) {
+            added.push(json!({ "id": id, "version": new.version }));
+        }
+    }
+
+    let cache_version_changed =
+        old_manifest.cache_version.to_string() != new_manifest.cache_version.to_string();
+    let identical =
+        !cache_version_changed && added.is_empty() && removed.is_empty() && modified.is_empty();
+
+    let output = json!({
+        "old": {
+            "cache_version": old_manifest.cache_version,
+            "document_count": old_manifest.document_count,
+        },
+        "new": {
+            "cache_version": new_manifest.cache_version,
+            "document_count": new_manifest.document_count,
+        },
+        "cache_version_changed": cache_version_changed,
+        "document_count_delta": new_entries.len() as i64 - old_entries.len() as i64,
+        "added": added,
+        "removed": removed,
+        "modified": modified,
+        "identical": identical,
+    });
+
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    serde_json::to_writer_pretty(&mut out, &output)
+        .map_err(|e| CliError::internal(e))?;
+    writeln!(out).map_err(|e| CliError::io_error(e))?;
+
+    if args.exit_code && !identical {
+        return Err(CliError::differs("caches differ"));
+    }
+
+    Ok(())
+}
+
+/// Unified diff of a document's stored content between two caches.
+fn content_diff(
+    old_root: &Path,
+    old: &Entry,
+    new_root: &Path,
+    new: &Entry,
+    id: &str,
+) -> Result<String, CliError> {
+    let old_doc = cache::read_document(old_root, &old.file)?;
+    let new_doc = cache::read_document(new_root, &new.file)?;
+    let old_content = content_of(&old_doc);
+    let new_content = content_of(&new_doc);
+
+    Ok(TextDiff::from_lines(old_content, new_content)
+        .unified_diff()
+        .context_radius(3)
+        .header(&format!("a/{id}"), &format!("b/{id}"))
+        .to_string())
+}
+
+fn content_of(doc: &Value) -> &str {
+    doc["content"].as_str().unwrap_or_default()
+}
@@ -0,0 +1,212 @@
+use std::io::Write;
+use std::path::PathBuf;
+
+use clap::Args;
+use serde_json::{json, Value};
+
+use context_core::cache::ContextCache;
+use context_core::selection::ContextSelector;
+use context_core::types::Query;
+
+use crate::cache;
+use crate::config;
+use crate::exit_codes::CliError;
+use crate::queries::{self, BatchQuery, NormalizedQuery};
+
+#[derive(Args)]
+pub struct DiffResolveArgs {
+    /// Baseline cache directory
+    pub old: PathBuf,
+
+    /// Cache directory to compare against the baseline
+    pub new: PathBuf,
+
+    /// Search query (empty string is allowed)
+    #[arg(long, required_unless_present = "queries", conflicts_with = "queries")]
+    pub query: Option<String>,
+
+    /// Maximum token budget (default for query-file lines without one)
+    #[arg(long)]
+    pub budget: Option<usize>,
+
+    /// JSON-lines file of {"query", "budget", "id"} objects
+    #[arg(long, value_name = "FILE")]
+    pub queries: Option<PathBuf>,
+
+    /// Exit with code 1 when any selection differs
+    #[arg(long)]
+    pub exit_code: bool,
+}
+
+pub fn run(args: DiffResolveArgs) -> Result<(), CliError> {
+    let batch = match (&args.queries, args.query) {
+        (Some(path), _) => queries::read_query_file(path, args.budget)?,
+        (None, query) => {
+            let normalized = NormalizedQuery::parse(&query.unwrap_or_default())?;
+            vec![BatchQuery {
+                id: None,
+                query: normalized.text.clone(),
+                normalized,
+                budget: config::required(args.budget, "--budget")?,
+                fields: Value::Null,
+            }]
+        }
+    };
+
+    // Load both caches once
+    let old_cache = cache::load_cache(&args.old)?;
+    let new_cache = cache::load_cache(&args.new)?;
+
+    let selector = ContextSelector::default();
+    let mut reports = Vec::new();
+    let mut changed = 0usize;
+    for entry in &batch {
+        let old = select(&selector, &old_cache, entry)?;
+        let new = select(&selector, &new_cache, entry)?;
+        let report = compare(entry, &old, &new);
+        if !report["unchanged"].as_bool().unwrap_or(true) {
+            changed += 1;
+        }
+        reports.push(report);
+    }
+
+    let output = json!({
+        "queries": reports,
+        "changed_queries": changed,
+    });
+
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    serde_json::to_writer_pretty(&mut out, &output)
+        .map_err(|e| CliError::internal(e))?;
+    writeln!(out).map_err(|e| CliError::io_error(e))?;
+
+    if args.exit_code && changed > 0 {
+        return Err(CliError::differs(format!(
+            "selection differs for {changed} quer{}",
+            if changed == 1 { "y" } else { "ies" }
+        )));
+    }
+
+    Ok(())
+}
+
+/// Run selection and return the serialized `SelectionResult`, with each
+/// selected document's manifest `version` so content changes show up.
+pub fn select(
+    selector: &ContextSelector,
+    cache: &ContextCache,
+    entry: &BatchQuery,
+) -> Result<Value, CliError> {
+    let result = selector.select(cache, Query::new(&entry.query), entry.budget)?;
+    let mut result = serde_json::to_value(&result).map_err(|e| CliError::internal(e))?;
+    for doc in result["documents"].as_array_mut().into_iter().flatten() {
+        let id = doc["id"].as_str().unwrap_or_default().to_string();
+        let version = cache
+            .manifest
+            .documents
+            .iter()
+            .find(|entry| entry.id.to_string() == id)
+            .map(|entry| json!(entry.version))
+            .unwrap_or(Value::Null);
+        doc["version"] = version;
+    }
+    Ok(result)
+}
+
+/// The selected document with `id`, or null.
+fn selected_doc<'a>(result: &'a Value, id: &str) -> &'a Value {
+    result["documents"]
+        .as_array()
+        .and_then(|docs| docs.iter().find(|doc| doc["id"] == id))
+        .unwrap_or(&Value::Null)
+}
+
+/// Selected document IDs in rank order.
+fn selected_ids(result: &Value) -> Vec<String> {
+    result["documents"]
+        .as_array()
+        .map(|docs| {
+            docs.iter()
+                .filter_map(|doc| doc["id"].as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Total tokens of the selected documents.
+fn tokens_used(result: &Value) -> u64 {
+    result["documents"]
+        .as_array()
+        .map(|docs| docs.iter().filter_map(|doc| doc["tokens"].as_u64()).sum())
+        .unwrap_or(0)
+}
+
+/// Report which documents entered, left, changed rank, or changed content
+/// (version or tokens) between two selections for the same query. Ranks
+/// are 1-based.
+pub fn compare(entry: &BatchQuery, old: &Value, new: &Value) -> Value {
+    let old_ids = selected_ids(old);
+    let new_ids = selected_ids(new);
+    let rank = |ids: &[String], id: &str| ids.iter().position(|i| i == id).map(|p| p + 1);
+
+    let entered: Vec<Value> = new_ids
+        .iter()
+        .enumerate()
+        .filter(|(_, id)| !old_ids.contains(id))
+        .map(|(i, id)| json!({ "id": id, "rank": i + 1 }))
+        .collect();
+    let left: Vec<Value> = old_ids
+        .iter()
+        .enumerate()
+        .filter(|(_, id)| !new_ids.contains(id))
+        .map(|(i, id)| json!({ "id": id, "rank": i + 1 }))
+        .collect();
+    let moved: Vec<Value> = new_ids
+        .iter()
+        .enumerate()
+        .filter_map(|(i, id)| {
+            let old_rank = rank(&old_ids, id)?;
+            let new_rank = i + 1;
+            (old_rank != new_rank)
+                .then(|| json!({ "id": id, "old_rank": old_rank, "new_rank": new_rank }))
+        })
+        .collect();
+    let changed: Vec<Value> = new_ids
+        .iter()
+        .enumerate()
+        .filter(|(_, id)| old_ids.contains(id))
+        .filter_map(|(i, id)| {
+            let (old_doc, new_doc) = (selected_doc(old, id), selected_doc(new, id));
+            (old_doc["version"] != new_doc["version"] || old_doc["tokens"] != new_doc["tokens"])
+                .then(|| {
+                    json!({
+                        "id": id,
+                        "rank": i + 1,
+                        "old_version": old_doc["version"],
+                        "new_version": new_doc["version"],
+                        "old_tokens": old_doc["tokens"],
+                        "new_tokens": new_doc["tokens"],
+                    })
+                })
+        })
+        .collect();
+    let unchanged = old_ids == new_ids && changed.is_empty();
+
+    let old_tokens = tokens_used(old);
+    let new_tokens = tokens_used(new);
+
+    json!({
+        "id": entry.id,
+        "query": entry.query,
+        "budget": entry.budget,
+        "entered": entered,
+        "left": left,
+        "moved": moved,
+        "changed": changed,
+        "old_tokens": old_tokens,
+        "new_tokens": new_tokens,
+        "token_delta": new_tokens as i64 - old_tokens as i64,
+        "unchanged": unchanged,
+    })
+}
@@ -0,0 +1,228 @@
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::PathBuf;
+
+use clap::{Args, ValueEnum};
+use serde_json::{json, Value};
+
+use context_core::selection::ContextSelector;
+use context_core::types::Query;
+
+use crate::cache;
+use crate::config::{self, Config};
+use crate::exit_codes::{CliError, USAGE_ERROR};
+use crate::queries;
+
+#[derive(ValueEnum, Clone, Copy, Default)]
+pub enum EvalFormat {
+    #[default]
+    Json,
+    Table,
+}
+
+#[derive(Args)]
+pub struct EvalArgs {
+    /// Path to a built cache directory
+    #[arg(long)]
+    pub cache: Option<PathBuf>,
+
+    /// JSON-lines file of {"query", "budget", "relevant": [ids], "id"} judgments
+    #[arg(long, value_name = "FILE")]
+    pub judgments: PathBuf,
+
+    /// Budget for judgments without one
+    #[arg(long)]
+    pub budget: Option<usize>,
+
+    /// Output format
+    #[arg(long, value_enum, default_value = "json")]
+    pub format: EvalFormat,
+
+    /// Fail when mean recall across all judgments is below this value (0.0-1.0)
+    #[arg(long, value_name = "RECALL")]
+    pub min_recall: Option<f64>,
+}
+
+impl EvalArgs {
+    /// Fill options not given on the command line from `context.toml`.
+    pub fn with_config(mut self, config: &Config) -> Self {
+        self.cache = self.cache.or_else(|| config.resolve_cache());
+        self
+    }
+}
+
+/// Relevance metrics for one judged query.
+struct Metrics {
+    recall: f64,
+    precision: f64,
+    reciprocal_rank: f64,
+    ndcg: f64,
+}
+
+impl Metrics {
+    /// Compute binary-relevance metrics for `selected` (in rank order).
+    fn compute(selected: &[String], relevant: &BTreeSet<String>) -> Self {
+        let hits: Vec<bool> = selected.iter().map(|id| relevant.contains(id)).collect();
+        let hit_count = hits.iter().filter(|&&hit| hit).count();
+
+        let recall = hit_count as f64 / relevant.len() as f64;
+        let precision = if selected.is_empty() {
+            0.0
+        } else {
+            hit_count as f64 / selected.len() as f64
+        };
+        let reciprocal_rank = hits
+            .iter()
+            .position(|&hit| hit)
+            .map_or(0.0, |i| 1.0 / (i + 1) as f64);
+
+        let discount = |i: usize| 1.0 / ((i + 2) as f64).log2();
+        let dcg: f64 = hits
+            .iter()
+            .enumerate()
+            .filter(|(_, hit)| **hit)
+            .map(|(i, _)| discount(i))
+            .sum();
+        let ideal: f64 = (0..relevant.len().min(selected.len())).map(discount).sum();
+        let ndcg = if ideal > 0.0 { dcg / ideal } else { 0.0 };
+
+        Self {
+            recall,
+            precision,
+            reciprocal_rank,
+            ndcg,
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "recall": self.recall,
+            "precision": self.precision,
+            "mrr": self.reciprocal_rank,
+            "ndcg": self.ndcg,
+        })
+    }
+}
+
+pub fn run(args: EvalArgs) -> Result<(), CliError> {
+    let cache_path = config::required(args.cache, "--cache")?;
+    let judgments = queries::read_query_file(&args.judgments, args.budget)?;
+
+    // Load manifest
+    let cache = cache::load_cache(&cache_path)?;
+
+    let selector = ContextSelector::default();
+    let mut rows = Vec::new();
+    for (index, judgment) in judgments.iter().enumerate() {
+        let relevant: BTreeSet<String> = judgment.fields["relevant"]
+            .as_array()
+            .map(|ids| {
+                ids.iter()
+                    .filter_map(|id| id.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+        if relevant.is_empty() {
+            return Err(CliError::new(
+                USAGE_ERROR,
+                format!(
+                    "{}: judgment {} needs a non-empty \"relevant\" list of document IDs",
+                    args.judgments.display(),
+                    index + 1
+                ),
+            ));
+        }
+
+        let result = selector.select(&cache, Query::new(&judgment.query), judgment.budget)?;
+        let result = serde_json::to_value(&result).map_err(|e| CliError::internal(e))?;
+        let selected: Vec<String> = result["documents"]
+            .as_array()
+            .map(|docs| {
+                docs.iter()
+                    .filter_map(|doc| doc["id"].as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let metrics = Metrics::compute(&selected, &relevant);
+        rows.push((judgment, selected, metrics));
+    }
+
+    let count = rows.len().max(1) as f64;
+    let mean = |f: fn(&Metrics) -> f64| rows.iter().map(|(_, _, m)| f(m)).sum::<f64>() / count;
+    let aggregate = Metrics {
+        recall: mean(|m| m.recall),
+        precision: mean(|m| m.precision),
+        reciprocal_rank: mean(|m| m.reciprocal_rank),
+        ndcg: mean(|m| m.ndcg),
+    };
+
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    match args.format {
+        EvalFormat::Json => {
+            let queries: Vec<Value> = rows
+                .iter()
+                .map(|(judgment, selected, metrics)| {
+                    json!({
+                        "id": judgment.id,
+                        "query": judgment.query,
+                        "budget": judgment.budget,
+                        "selected": selected,
+                        "metrics": metrics.to_json(),
+                    })
+                })
+                .collect();
+            let output = json!({
+                "queries": queries,
+                "aggregate": aggregate.to_json(),
+                "query_count": rows.len(),
+            });
+            serde_json::to_writer_pretty(&mut out, &output)
+                .map_err(|e| CliError::internal(e))?;
+            writeln!(out).map_err(|e| CliError::io_error(e))?;
+        }
+        EvalFormat::Table => {
+            let label = |judgment: &queries::BatchQuery| match &judgment.id {
+                Some(Value::String(id)) => id.clone(),
+                Some(id) => id.to_string(),
+                None => judgment.query.clone(),
+            };
+            let width = rows
+                .iter()
+                .map(|(judgment, _, _)| label(judgment).chars().count())
+                .max()
+                .unwrap_or(0)
+                .max("query".len());
+            let line = |name: &str, m: &Metrics| {
+                format!(
+                    "{name:<width$}  {:>7.4}  {:>9.4}  {:>6.4}  {:>6.4}",
+                    m.recall, m.precision, m.reciprocal_rank, m.ndcg
+                )
+            };
+
+            writeln!(
+                out,
+                "{:<width$}  {:>7}  {:>9}  {:>6}  {:>6}",
+                "query", "recall", "precision", "mrr", "ndcg"
+            )
+            .map_err(|e| CliError::io_error(e))?;
+            for (judgment, _, metrics) in &rows {
+                writeln!(out, "{}", line(&label(judgment), metrics))
+                    .map_err(|e| CliError::io_error(e))?;
+            }
+            writeln!(out, "{}", line("mean", &aggregate)).map_err(|e| CliError::io_error(e))?;
+        }
+    }
+
+    if let Some(min_recall) = args.min_recall {
+        if aggregate.recall < min_recall {
+            return Err(CliError::threshold_failed(format!(
+                "mean recall {:.4} is below --min-recall {min_recall}",
+                aggregate.recall
+            )));
+        }
+    }
+
+    Ok(())
+}
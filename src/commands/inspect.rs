@@ -1,33 +1,162 @@
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use clap::Args;
-use serde_json::json;
+use clap::{Args, ValueEnum};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 
 use context_core::cache::CacheManifest;
 
-use crate::exit_codes::{self, CliError};
+use crate::cache;
+use crate::config::{self, Config};
+use crate::exit_codes::{CliError, USAGE_ERROR};
+use crate::verify;
 
-#[derive(Args)]
+#[derive(Args, Default)]
 pub struct InspectArgs {
     /// Path to a built cache directory
     #[arg(long)]
-    pub cache: PathBuf,
+    pub cache: Option<PathBuf>,
+
+    /// Re-hash every document, detect orphaned files, and recompute the cache version
+    #[arg(long)]
+    pub deep: bool,
+
+    /// Print one JSON line per document instead of the cache summary
+    #[arg(long, conflicts_with = "deep")]
+    pub documents: bool,
+
+    #[command(flatten)]
+    pub listing: ListingOptions,
+}
+
+/// Options for the per-document listing (`inspect --documents`, `ls`).
+#[derive(Args, Default)]
+pub struct ListingOptions {
+    /// Sort documents by this key (ties are broken by ID) [default: id]
+    #[arg(long, value_enum)]
+    pub sort: Option<SortKey>,
+
+    /// Only list documents whose source path starts with this prefix
+    #[arg(long, value_name = "PATH")]
+    pub prefix: Option<String>,
+}
+
+impl ListingOptions {
+    /// Fill options not given on the command line from `context.toml`.
+    pub fn with_config(mut self, config: &Config) -> Self {
+        self.sort = self.sort.or(config.inspect.sort);
+        self.prefix = self.prefix.or_else(|| config.inspect.prefix.clone());
+        self
+    }
+}
+
+#[derive(ValueEnum, Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SortKey {
+    #[default]
+    Id,
+    Source,
+    Size,
+    Tokens,
+}
+
+/// Arguments for `context ls`, shorthand for `context inspect --documents`.
+#[derive(Args, Default)]
+pub struct ListArgs {
+    /// Path to a built cache directory
+    #[arg(long)]
+    pub cache: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub listing: ListingOptions,
+}
+
+impl From<ListArgs> for InspectArgs {
+    fn from(args: ListArgs) -> Self {
+        Self {
+            cache: args.cache,
+            documents: true,
+            listing: args.listing,
+            ..Self::default()
+        }
+    }
+}
+
+impl InspectArgs {
+    /// Fill options not given on the command line from `context.toml`.
+    pub fn with_config(mut self, config: &Config) -> Self {
+        self.cache = self.cache.or_else(|| config.inspect_cache());
+        // `deep` only applies to the summary, `sort` and `prefix` only to
+        // the document listing
+        if self.documents {
+            self.listing = self.listing.with_config(config);
+        } else {
+            self.deep = self.deep || config.inspect.deep.unwrap_or(false);
+        }
+        self
+    }
 }
 
 pub fn run(args: InspectArgs) -> Result<(), CliError> {
+    let cache_path = cache::resolve_root(&config::required(args.cache, "--cache")?);
+
+    if !args.documents {
+        for (flag, given) in [
+            ("--sort", args.listing.sort.is_some()),
+            ("--prefix", args.listing.prefix.is_some()),
+        ] {
+            if given {
+                return Err(CliError::new(
+                    USAGE_ERROR,
+                    format!("{flag} only applies to the document listing (--documents)"),
+                ));
+            }
+        }
+    }
+
     // Load manifest
-    let manifest_path = args.cache.join("manifest.json");
-    let manifest_file = std::fs::File::open(&manifest_path)
-        .map_err(|e| exit_codes::from_io_error(e, &args.cache))?;
-    let manifest: CacheManifest = serde_json::from_reader(manifest_file)
-        .map_err(exit_codes::from_manifest_parse)?;
+    let manifest = cache::load_manifest(&cache_path)?;
+
+    if args.documents {
+        return list_documents(&cache_path, &manifest, &args.listing);
+    }
 
+    let mut output = summary(&cache_path, &manifest);
+
+    // Handle --deep
+    let verification = args.deep.then(|| verify::verify(&cache_path, &manifest));
+    if let Some(verification) = &verification {
+        let all_files_exist = output["valid"].as_bool().unwrap_or(false);
+        output["valid"] = json!(all_files_exist && verification.is_valid());
+        output["verification"] = json!(verification);
+    }
+
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    serde_json::to_writer_pretty(&mut out, &output)
+        .map_err(|e| CliError::internal(e))?;
+    writeln!(out).map_err(|e| CliError::io_error(e))?;
+
+    if let Some(verification) = verification.filter(|v| !v.is_valid()) {
+        return Err(CliError::cache_invalid(format!(
+            "verification failed: {} document failure(s), {} orphaned file(s)",
+            verification.failures.len(),
+            verification.orphaned_files.len()
+        )));
+    }
+
+    Ok(())
+}
+
+/// The cache summary printed by `inspect`: version, document count, total
+/// bytes of the document files, and whether they all exist.
+pub fn summary(cache_path: &Path, manifest: &CacheManifest) -> Value {
     // Compute total_bytes from document files
     let mut total_bytes: u64 = 0;
     let mut all_files_exist = true;
     for entry in &manifest.documents {
-        let doc_path = args.cache.join(&entry.file);
+        let doc_path = cache_path.join(&entry.file);
         match std::fs::metadata(&doc_path) {
             Ok(meta) => total_bytes += meta.len(),
             Err(_) => {
@@ -36,18 +165,88 @@ pub fn run(args: InspectArgs) -> Result<(), CliError> {
         }
     }
 
-    let output = json!({
+    json!({
         "cache_version": manifest.cache_version,
         "document_count": manifest.document_count,
         "total_bytes": total_bytes,
         "valid": all_files_exist,
+    })
+}
+
+/// Write one JSON object per manifest entry, one per line.
+fn list_documents(
+    cache_path: &std::path::Path,
+    manifest: &CacheManifest,
+    options: &ListingOptions,
+) -> Result<(), CliError> {
+    let mut rows = Vec::new();
+    for entry in &manifest.documents {
+        let stored: Option<Value> = std::fs::read(cache_path.join(&entry.file))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok());
+        let bytes = std::fs::metadata(cache_path.join(&entry.file))
+            .ok()
+            .map(|meta| meta.len());
+        let source = stored
+            .as_ref()
+            .and_then(|doc| doc["source"].as_str())
+            .map(|s| s.replace('\\', "/"));
+
+        if let Some(prefix) = &options.prefix {
+            if !source.as_deref().is_some_and(|s| s.starts_with(prefix.as_str())) {
+                continue;
+            }
+        }
+
+        let tokens = cache::document_tokens(cache_path, &entry.file).ok();
+        let metadata = stored
+            .as_ref()
+            .map_or(Value::Null, |doc| doc["metadata"].clone());
+
+        rows.push(json!({
+            "id": entry.id.to_string(),
+            "source": source,
+            "file": entry.file,
+            "bytes": bytes,
+            "token_estimate": tokens,
+            "version": entry.version.to_string(),
+            "metadata": metadata,
+        }));
+    }
+
+    let key = |row: &Value| -> (Value, String) {
+        let sort_value = match options.sort.unwrap_or_default() {
+            SortKey::Id => Value::Null,
+            SortKey::Source => row["source"].clone(),
+            SortKey::Size => row["bytes"].clone(),
+            SortKey::Tokens => row["token_estimate"].clone(),
+        };
+        (sort_value, row["id"].as_str().unwrap_or_default().to_string())
+    };
+    rows.sort_by(|a, b| {
+        let (a_value, a_id) = key(a);
+        let (b_value, b_id) = key(b);
+        compare_values(&a_value, &b_value).then(a_id.cmp(&b_id))
     });
 
     let stdout = std::io::stdout();
     let mut out = stdout.lock();
-    serde_json::to_writer_pretty(&mut out, &output)
-        .map_err(|e| CliError::internal(e))?;
-    writeln!(out).map_err(|e| CliError::io_error(e))?;
+    for row in &rows {
+        serde_json::to_writer(&mut out, row).map_err(|e| CliError::internal(e))?;
+        writeln!(out).map_err(|e| CliError::io_error(e))?;
+    }
 
     Ok(())
 }
+
+/// Order sort keys: nulls first, then numbers or strings by value.
+fn compare_values(a: &Value, b: &Value) -> std::cmp::Ordering {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.as_u64().cmp(&b.as_u64()),
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        (Value::Null, Value::Null) => std::cmp::Ordering::Equal,
+        (Value::Null, _) => std::cmp::Ordering::Less,
+        (_, Value::Null) => std::cmp::Ordering::Greater,
+        _ => std::cmp::Ordering::Equal,
+    }
+}
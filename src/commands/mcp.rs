@@ -0,0 +1,171 @@
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+use clap::Args;
+use serde_json::{json, Value};
+
+use crate::commands::resolve::{self, CandidateArgs, Format, Resolver};
+use crate::config::{self, Config};
+use crate::exit_codes::{CliError, INVALID_BUDGET, INVALID_QUERY};
+use crate::federation::CacheSpec;
+use crate::queries::NormalizedQuery;
+
+/// Protocol revision announced when the client does not request one.
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// Name of the resolve tool, as in the MCP `context.resolve` spec.
+const RESOLVE_TOOL: &str = "context.resolve";
+
+// JSON-RPC 2.0 error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+#[derive(Args)]
+pub struct McpArgs {
+    /// Path to a built cache directory
+    #[arg(long)]
+    pub cache: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub candidates: CandidateArgs,
+}
+
+impl McpArgs {
+    /// Fill options not given on the command line from `context.toml`.
+    pub fn with_config(mut self, config: &Config) -> Self {
+        self.cache = self.cache.or_else(|| config.resolve_cache());
+        self.candidates = self.candidates.with_config(config);
+        self
+    }
+}
+
+/// Serve the Model Context Protocol over stdin/stdout, one JSON-RPC message
+/// per line, until stdin closes.
+pub fn run(args: McpArgs) -> Result<(), CliError> {
+    let cache_path = config::required(args.cache, "--cache")?;
+
+    // Load the cache once for the whole session
+    let specs = [CacheSpec::from(cache_path)];
+    let resolver = args.candidates.resolver(&specs)?;
+
+    let stdin = std::io::stdin();
+    let stdout = std::io::stdout();
+    for line in stdin.lock().lines() {
+        let line = line.map_err(|e| CliError::io_error(format!("reading stdin: {e}")))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Some(response) = handle(&resolver, &line) else {
+            continue;
+        };
+
+        let mut out = stdout.lock();
+        serde_json::to_writer(&mut out, &response).map_err(|e| CliError::internal(e))?;
+        writeln!(out).map_err(|e| CliError::io_error(e))?;
+        out.flush().map_err(|e| CliError::io_error(e))?;
+    }
+
+    Ok(())
+}
+
+/// Handle one message, returning the response, or `None` for notifications.
+fn handle(resolver: &Resolver, line: &str) -> Option<Value> {
+    let message: Value = match serde_json::from_str(line) {
+        Ok(message) => message,
+        Err(e) => return Some(rpc_error(Value::Null, PARSE_ERROR, &format!("parse error: {e}"))),
+    };
+
+    // Notifications carry no id and get no response
+    let id = message.get("id").cloned()?;
+    let Some(method) = message["method"].as_str() else {
+        return Some(rpc_error(id, INVALID_REQUEST, "missing method"));
+    };
+    let params = &message["params"];
+
+    let result = match method {
+        "initialize" => json!({
+            "protocolVersion": params["protocolVersion"].as_str().unwrap_or(PROTOCOL_VERSION),
+            "capabilities": { "tools": {} },
+            "serverInfo": {
+                "name": "context",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+        }),
+        "ping" => json!({}),
+        "tools/list" => json!({ "tools": [resolve_tool()] }),
+        "tools/call" => {
+            if params["name"] != RESOLVE_TOOL {
+                let name = params["name"].as_str().unwrap_or_default();
+                return Some(rpc_error(id, INVALID_PARAMS, &format!("unknown tool: {name}")));
+            }
+            call_resolve(resolver, &params["arguments"])
+        }
+        _ => {
+            let message = format!("method not found: {method}");
+            return Some(rpc_error(id, METHOD_NOT_FOUND, &message));
+        }
+    };
+
+    Some(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+}
+
+fn resolve_tool() -> Value {
+    json!({
+        "name": RESOLVE_TOOL,
+        "description": "Select documents from the context cache for a query within a token budget.",
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "query": { "type": "string", "description": "Search query" },
+                "budget": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "description": "Maximum token budget",
+                },
+            },
+            "required": ["query", "budget"],
+        },
+    })
+}
+
+/// Run `context.resolve`. The text content is exactly the JSON that
+/// `context resolve --format json` prints, without the trailing newline.
+/// Failures are tool errors whose text is the error body shared with
+/// `context serve`.
+fn call_resolve(resolver: &Resolver, arguments: &Value) -> Value {
+    let (text, is_error) = match resolve_text(resolver, arguments) {
+        Ok(text) => (text, false),
+        Err(e) => (e.to_json().to_string(), true),
+    };
+    json!({
+        "content": [{ "type": "text", "text": text }],
+        "isError": is_error,
+    })
+}
+
+fn resolve_text(resolver: &Resolver, arguments: &Value) -> Result<String, CliError> {
+    let query = arguments["query"]
+        .as_str()
+        .ok_or_else(|| CliError::new(INVALID_QUERY, "missing \"query\" string"))?;
+    let query = NormalizedQuery::parse(query)?;
+    let budget = arguments["budget"]
+        .as_u64()
+        .and_then(|b| usize::try_from(b).ok())
+        .ok_or_else(|| {
+            CliError::new(INVALID_BUDGET, "\"budget\" must be a non-negative integer")
+        })?;
+
+    let mut bytes = resolve::json_bytes(&resolver.select(&query, budget)?, Format::Json)?;
+    bytes.pop();
+    String::from_utf8(bytes).map_err(|e| CliError::internal(e))
+}
+
+fn rpc_error(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
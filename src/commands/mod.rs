@@ -1,3 +1,10 @@
 pub mod build;
+pub mod cat;
+pub mod config;
+pub mod diff;
+pub mod diff_resolve;
+pub mod eval;
 pub mod inspect;
+pub mod mcp;
 pub mod resolve;
+pub mod serve;
@@ -1,67 +1,423 @@
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use clap::{Args, ValueEnum};
+use serde::ser::{SerializeMap, SerializeSeq};
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::Value;
 
-use context_core::cache::{CacheManifest, ContextCache};
+use context_core::cache::ContextCache;
 use context_core::selection::ContextSelector;
-use context_core::types::Query;
+use context_core::types::{Query, SelectionResult};
 
-use crate::exit_codes::{self, CliError};
+use crate::config::{self, Config};
+use crate::exit_codes::{CliError, USAGE_ERROR};
+use crate::explain;
+use crate::federation::{CacheSpec, CacheSummary, Federation};
+use crate::filters::{DocumentFilter, FilterSummary, MetadataFilter};
+use crate::pins::{PinSummary, PinnedDocument, Pins};
+use crate::queries::{self, BatchQuery, NormalizedQuery, QueryNormalization};
+use crate::render::{self, HeaderField, Template};
 
-#[derive(ValueEnum, Clone)]
+#[derive(ValueEnum, Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
 pub enum Format {
+    #[default]
     Json,
     Pretty,
+    Markdown,
+    Text,
+    Xml,
 }
 
-#[derive(Args)]
+#[derive(Args, Default)]
 pub struct ResolveArgs {
-    /// Path to a built cache directory
+    /// Path to a built cache directory. Repeat to resolve across several
+    /// caches as one pool; IDs are then prefixed `ALIAS/` (default alias: the
+    /// directory name)
+    #[arg(long, value_name = "[ALIAS=]DIR")]
+    pub cache: Vec<CacheSpec>,
+
+    /// Search query (empty string is allowed; normalized to NFC with collapsed
+    /// whitespace); `-` reads it from stdin
     #[arg(long)]
-    pub cache: PathBuf,
+    pub query: Option<String>,
+
+    /// Read the query from a UTF-8 file
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["query", "queries"])]
+    pub query_file: Option<PathBuf>,
 
-    /// Search query (empty string is allowed)
+    /// Maximum token budget (minimum: 0; default for --queries lines without one)
     #[arg(long)]
-    pub query: String,
+    pub budget: Option<usize>,
+
+    /// JSON-lines file of {"query", "budget", "id"} objects; writes one compact
+    /// result per line, in input order
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["query", "format"])]
+    pub queries: Option<PathBuf>,
 
-    /// Maximum token budget (minimum: 0)
+    /// Output format [default: json]
     #[arg(long)]
-    pub budget: usize,
+    pub format: Option<Format>,
+
+    /// Wrap the result as {"result", "explain"}, where "explain" gives every
+    /// document's score, tokens, rank, and inclusion reason
+    #[arg(long, conflicts_with_all = ["queries", "template"])]
+    pub explain: bool,
+
+    /// Header fields shown per document by markdown, text, and xml output
+    /// [default: id,source,score]
+    #[arg(long, value_delimiter = ',', value_name = "FIELDS")]
+    pub header: Option<Vec<HeaderField>>,
+
+    /// Text between documents in markdown, text, and xml output; `\n` and
+    /// `\t` are interpreted
+    #[arg(long, value_name = "TEXT")]
+    pub separator: Option<String>,
 
-    /// Output format
-    #[arg(long, default_value = "json")]
-    pub format: Format,
+    #[command(flatten)]
+    pub candidates: CandidateArgs,
+
+    /// Render with a template file instead of --format (see README)
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["queries", "format"])]
+    pub template: Option<PathBuf>,
+}
+
+impl ResolveArgs {
+    /// Fill options not given on the command line from `context.toml`, then
+    /// apply built-in defaults.
+    pub fn with_config(mut self, config: &Config) -> Self {
+        let resolve = &config.resolve;
+        if self.cache.is_empty() {
+            self.cache.extend(config.resolve_cache().map(CacheSpec::from));
+        }
+        if self.query_file.is_none() {
+            self.query = self.query.or_else(|| resolve.query.clone());
+        }
+        self.budget = self.budget.or(resolve.budget);
+        self.candidates = self.candidates.with_config(config);
+        self.header = self.header.or_else(|| resolve.header.clone());
+        self.separator = self.separator.or_else(|| resolve.separator.clone());
+        if self.format.is_none() {
+            self.template = self.template.or_else(|| resolve.template.clone());
+        }
+        self.format = self.format.or(resolve.format).or(Some(Format::default()));
+        self
+    }
+}
+
+/// Options that shape the candidate pool, shared by every command that
+/// resolves.
+#[derive(Args, Default)]
+pub struct CandidateArgs {
+    /// Only consider documents whose metadata matches KEY=VALUE (or does not,
+    /// with KEY!=VALUE); list fields match if any item does. Repeatable
+    #[arg(long = "filter", value_name = "KEY=VALUE")]
+    pub filters: Vec<MetadataFilter>,
+
+    /// Only consider documents whose source path starts with PREFIX.
+    /// Repeatable; a document must match one
+    #[arg(long = "path-prefix", value_name = "PREFIX")]
+    pub path_prefixes: Vec<String>,
+
+    /// Never select the document with this ID. Repeatable
+    #[arg(long = "exclude-id", value_name = "ID")]
+    pub exclude_ids: Vec<String>,
+
+    /// Always include this document, reserving its tokens before selection.
+    /// Repeatable; adds to `pins` in context.toml
+    #[arg(long = "pin", value_name = "ID")]
+    pub pins: Vec<String>,
+}
+
+impl CandidateArgs {
+    /// Fill filters not given on the command line from `context.toml`, and
+    /// put its `pins` ahead of those given on the command line.
+    pub fn with_config(mut self, config: &Config) -> Self {
+        let resolve = &config.resolve;
+        if self.filters.is_empty() {
+            self.filters = resolve.filters.clone().unwrap_or_default();
+        }
+        if self.path_prefixes.is_empty() {
+            self.path_prefixes = resolve.path_prefixes.clone().unwrap_or_default();
+        }
+        if self.exclude_ids.is_empty() {
+            self.exclude_ids = resolve.exclude_ids.clone().unwrap_or_default();
+        }
+        if let Some(pins) = &resolve.pins {
+            let cli_pins = std::mem::take(&mut self.pins);
+            self.pins = pins.iter().cloned().chain(cli_pins).collect();
+        }
+        self
+    }
+
+    /// Load `specs` as a resolver with these filters and pins.
+    pub fn resolver(self, specs: &[CacheSpec]) -> Result<Resolver, CliError> {
+        let filter = DocumentFilter {
+            metadata: self.filters,
+            path_prefixes: self.path_prefixes,
+            exclude_ids: self.exclude_ids,
+        };
+        Resolver::load(specs, filter, &self.pins)
+    }
+}
+
+/// A loaded candidate pool: the cache (or merged caches), with pinned
+/// documents set aside and filters applied, ready for repeated selection.
+pub struct Resolver {
+    federation: Federation,
+    filter: DocumentFilter,
+    pins: Pins,
+}
+
+impl Resolver {
+    pub fn load(
+        specs: &[CacheSpec],
+        filter: DocumentFilter,
+        pins: &[String],
+    ) -> Result<Self, CliError> {
+        // Load manifest, merging caches when several are given
+        let mut federation = Federation::load(specs)?;
+
+        // Handle --pin, then --filter, --path-prefix, and --exclude-id
+        let pins = Pins::take(&mut federation.cache, pins)?;
+        filter.apply(&mut federation.cache)?;
+
+        Ok(Resolver {
+            federation,
+            filter,
+            pins,
+        })
+    }
+
+    pub fn cache(&self) -> &ContextCache {
+        &self.federation.cache
+    }
+
+    /// Run selection, recording query normalization, filters, pins, and
+    /// federated caches alongside the `SelectionResult`.
+    pub fn select(&self, query: &NormalizedQuery, budget: usize) -> Result<Resolved, CliError> {
+        let selection_budget = self.pins.remaining(budget)?;
+        let selector = ContextSelector::default();
+        let result = selector.select(self.cache(), Query::new(&query.text), selection_budget)?;
+
+        let caches = result
+            .documents
+            .iter()
+            .map(|document| self.federation.alias_of(&document.id.to_string()))
+            .collect();
+        Ok(Resolved {
+            result,
+            pinned: self.pins.entries.clone(),
+            caches,
+            annotations: Annotations {
+                query_normalization: query.normalization(),
+                filters: self.filter.summary(),
+                pinned: self.pins.summary(selection_budget),
+                caches: self.federation.summary(),
+            },
+        })
+    }
+
+    /// `--explain` output: the result together with its explanation.
+    pub fn explain(&self, query: &NormalizedQuery, budget: usize) -> Result<Explained, CliError> {
+        let result = self.select(query, budget)?;
+        let explain = explain::explain(self.cache(), &query.text, budget, &result.to_value()?)?;
+        Ok(Explained { result, explain })
+    }
+}
+
+/// A `SelectionResult` with what shaped it: pinned documents ahead of the
+/// selected ones, the cache each selected document came from when caches
+/// are merged, and the query normalization, filters, pins, and caches in
+/// the `selection` block. The engine's own fields serialize exactly as the
+/// `SelectionResult` does on its own.
+pub struct Resolved {
+    result: SelectionResult,
+    pinned: Vec<PinnedDocument>,
+    /// Cache alias of each selected document, in result order.
+    caches: Vec<Option<String>>,
+    annotations: Annotations,
+}
+
+impl Resolved {
+    /// The result as a JSON value, for reading fields back.
+    pub fn to_value(&self) -> Result<Value, CliError> {
+        serde_json::to_value(self).map_err(|e| CliError::internal(e))
+    }
+}
+
+impl Serialize for Resolved {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(2))?;
+        map.serialize_entry("documents", &ResolvedDocuments(self))?;
+        map.serialize_entry(
+            "selection",
+            &Annotated {
+                engine: &self.result.selection,
+                annotations: &self.annotations,
+            },
+        )?;
+        map.end()
+    }
+}
+
+/// Keys added to the engine's `selection` block, each only when it applies.
+#[derive(Serialize)]
+struct Annotations {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    query_normalization: Option<QueryNormalization>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    filters: Option<FilterSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pinned: Option<PinSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    caches: Option<Vec<CacheSummary>>,
+}
+
+#[derive(Serialize)]
+struct Annotated<'a, T> {
+    #[serde(flatten)]
+    engine: &'a T,
+    #[serde(flatten)]
+    annotations: &'a Annotations,
+}
+
+/// Pinned documents, then the selected documents with their cache alias.
+struct ResolvedDocuments<'a>(&'a Resolved);
+
+impl Serialize for ResolvedDocuments<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let resolved = self.0;
+        let documents = &resolved.result.documents;
+        let mut seq = serializer.serialize_seq(Some(resolved.pinned.len() + documents.len()))?;
+        for pinned in &resolved.pinned {
+            seq.serialize_element(pinned)?;
+        }
+        for (document, cache) in documents.iter().zip(&resolved.caches) {
+            seq.serialize_element(&WithCache {
+                document,
+                cache: cache.as_deref(),
+            })?;
+        }
+        seq.end()
+    }
+}
+
+#[derive(Serialize)]
+struct WithCache<'a, T> {
+    #[serde(flatten)]
+    document: &'a T,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cache: Option<&'a str>,
+}
+
+/// `--explain` output.
+#[derive(Serialize)]
+pub struct Explained {
+    result: Resolved,
+    explain: Value,
 }
 
 pub fn run(args: ResolveArgs) -> Result<(), CliError> {
-    // Load manifest
-    let manifest_path = args.cache.join("manifest.json");
-    let manifest_file = std::fs::File::open(&manifest_path)
-        .map_err(|e| exit_codes::from_io_error(e, &args.cache))?;
-    let manifest: CacheManifest = serde_json::from_reader(manifest_file)
-        .map_err(exit_codes::from_manifest_parse)?;
-
-    let cache = ContextCache {
-        root: args.cache,
-        manifest,
+    let specs = config::required(Some(args.cache).filter(|c| !c.is_empty()), "--cache")?;
+
+    // Handle --queries
+    if let Some(path) = &args.queries {
+        let batch = queries::read_query_file(path, args.budget)?;
+        let resolver = args.candidates.resolver(&specs)?;
+        return run_batch(&resolver, &batch);
+    }
+
+    // Handle --query-file and --query -
+    let raw = match (&args.query_file, args.query) {
+        (Some(path), _) => queries::read_query_text(path)?,
+        (None, Some(query)) if query == "-" => queries::read_query_text(Path::new("-"))?,
+        (None, query) => config::required(query, "--query")?,
     };
+    let query = NormalizedQuery::parse(&raw)?;
+    let budget = config::required(args.budget, "--budget")?;
+    let format = args.format.unwrap_or_default();
+
+    let resolver = args.candidates.resolver(&specs)?;
+
+    // Handle --explain
+    if args.explain {
+        if !matches!(format, Format::Json | Format::Pretty) {
+            return Err(CliError::new(USAGE_ERROR, "--explain requires --format json or pretty"));
+        }
+        return write_output(&resolver.explain(&query, budget)?, format);
+    }
 
     // Run selection
-    let selector = ContextSelector::default();
-    let query = Query::new(&args.query);
-    let result = selector.select(&cache, query, args.budget)?;
+    let result = resolver.select(&query, budget)?;
+    let cache = resolver.cache();
+    let query = query.text;
+
+    // Handle --template
+    if let Some(path) = &args.template {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| CliError::io_error(format!("reading {}: {e}", path.display())))?;
+        let template = Template::parse(&source)?;
+        let docs = render::selected(cache, &result.to_value()?)?;
+        return write_text(&template.render(&docs, &query, budget));
+    }
+
+    if matches!(format, Format::Json | Format::Pretty) {
+        return write_output(&result, format);
+    }
+
+    // Prompt-ready formats
+    let header = args.header.as_deref().unwrap_or(HeaderField::DEFAULT);
+    let separator = args.separator.as_deref().map(render::unescape);
+    let docs = render::selected(cache, &result.to_value()?)?;
+    let rendered = match format {
+        Format::Markdown => {
+            render::markdown(&docs, header, separator.as_deref().unwrap_or("\n---\n\n"))
+        }
+        Format::Text => render::text(&docs, header, separator.as_deref().unwrap_or("\n")),
+        _ => render::xml(&docs, header, separator.as_deref().unwrap_or(""), &query, budget),
+    };
+    write_text(&rendered)
+}
 
-    // Serialize to stdout
+/// Write already-rendered text to stdout as-is.
+fn write_text(text: &str) -> Result<(), CliError> {
     let stdout = std::io::stdout();
     let mut out = stdout.lock();
-    match args.format {
-        Format::Json => serde_json::to_writer(&mut out, &result)
-            .map_err(|e| CliError::internal(e))?,
-        Format::Pretty => serde_json::to_writer_pretty(&mut out, &result)
-            .map_err(|e| CliError::internal(e))?,
-    }
-    writeln!(out).map_err(|e| CliError::io_error(e))?;
+    out.write_all(text.as_bytes()).map_err(|e| CliError::io_error(e))?;
+    out.flush().map_err(|e| CliError::io_error(e))
+}
+
+/// Serialize `value` in a JSON `format`, with the trailing newline the CLI
+/// prints.
+pub fn json_bytes<T: Serialize>(value: &T, format: Format) -> Result<Vec<u8>, CliError> {
+    let mut bytes = match format {
+        Format::Pretty => serde_json::to_vec_pretty(value).map_err(|e| CliError::internal(e))?,
+        _ => serde_json::to_vec(value).map_err(|e| CliError::internal(e))?,
+    };
+    bytes.push(b'\n');
+    Ok(bytes)
+}
+
+/// Serialize `value` to stdout in `format`.
+fn write_output<T: Serialize>(value: &T, format: Format) -> Result<(), CliError> {
+    let bytes = json_bytes(value, format)?;
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    out.write_all(&bytes).map_err(|e| CliError::io_error(e))?;
+
+    Ok(())
+}
 
+/// Resolve every query against one loaded cache, writing each result as a
+/// compact JSON line identical to single-query `--format json` output.
+fn run_batch(resolver: &Resolver, batch: &[BatchQuery]) -> Result<(), CliError> {
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    for entry in batch {
+        let result = resolver.select(&entry.normalized, entry.budget)?;
+        out.write_all(&json_bytes(&result, Format::Json)?).map_err(|e| CliError::io_error(e))?;
+    }
     Ok(())
 }
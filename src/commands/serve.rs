@@ -0,0 +1,176 @@
+use std::io::Read;
+use std::path::PathBuf;
+
+use clap::Args;
+use serde_json::Value;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use context_core::cache::CacheManifest;
+
+use crate::cache;
+use crate::commands::inspect;
+use crate::commands::resolve::{self, CandidateArgs, Format, Resolver};
+use crate::config::{self, Config};
+use crate::exit_codes::{CliError, CACHE_MISSING, INVALID_BUDGET, INVALID_QUERY, USAGE_ERROR};
+use crate::federation::CacheSpec;
+use crate::queries::NormalizedQuery;
+
+/// Largest request body accepted, in bytes.
+const MAX_BODY_BYTES: u64 = 1024 * 1024;
+
+/// Fields accepted in a `POST /resolve` body.
+const RESOLVE_FIELDS: &[&str] = &["query", "budget", "format", "explain"];
+
+#[derive(Args)]
+pub struct ServeArgs {
+    /// Path to a built cache directory
+    #[arg(long)]
+    pub cache: Option<PathBuf>,
+
+    /// Address to listen on; port 0 picks a free port
+    #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:7700")]
+    pub listen: String,
+
+    #[command(flatten)]
+    pub candidates: CandidateArgs,
+}
+
+impl ServeArgs {
+    /// Fill options not given on the command line from `context.toml`.
+    pub fn with_config(mut self, config: &Config) -> Self {
+        self.cache = self.cache.or_else(|| config.resolve_cache());
+        self.candidates = self.candidates.with_config(config);
+        self
+    }
+}
+
+pub fn run(args: ServeArgs) -> Result<(), CliError> {
+    let cache_path = config::required(args.cache, "--cache")?;
+
+    // Load the cache once for all requests. Pins and filters remove entries
+    // from the resolver's manifest, so /inspect summarizes its own copy
+    let specs = [CacheSpec::from(cache_path)];
+    let resolver = args.candidates.resolver(&specs)?;
+    let manifest = cache::load_manifest(&resolver.cache().root)?;
+
+    let server = Server::http(&args.listen)
+        .map_err(|e| CliError::io_error(format!("listening on {}: {e}", args.listen)))?;
+    match server.server_addr().to_ip() {
+        Some(addr) => eprintln!("Listening on http://{addr}"),
+        None => eprintln!("Listening on {}", args.listen),
+    }
+
+    for mut request in server.incoming_requests() {
+        let (status, body) = match handle(&resolver, &manifest, &mut request) {
+            Ok(response) => response,
+            Err(e) => (status_for(&e), error_body(&e)),
+        };
+        let content_type = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+            .expect("static header is valid");
+        let response = Response::from_data(body)
+            .with_status_code(status)
+            .with_header(content_type);
+        // A client that hung up does not stop the server
+        let _ = request.respond(response);
+    }
+
+    Ok(())
+}
+
+/// Route a request, returning the status and body. Successful bodies are
+/// the same bytes the equivalent CLI command prints.
+fn handle(
+    resolver: &Resolver,
+    manifest: &CacheManifest,
+    request: &mut Request,
+) -> Result<(u16, Vec<u8>), CliError> {
+    let path = request.url().split('?').next().unwrap_or_default().to_string();
+    let method = request.method().clone();
+    let body = match (&method, path.as_str()) {
+        (Method::Post, "/resolve") => {
+            let body = read_body(request)?;
+            resolve(resolver, &body)?
+        }
+        (Method::Get, "/inspect") => {
+            let summary = inspect::summary(&resolver.cache().root, manifest);
+            resolve::json_bytes(&summary, Format::Pretty)?
+        }
+        (method, "/resolve" | "/inspect") => {
+            let e = CliError::new(USAGE_ERROR, format!("method {method} not allowed for {path}"));
+            return Ok((405, error_body(&e)));
+        }
+        _ => {
+            let e = CliError::new(USAGE_ERROR, format!("no such endpoint: {path}"));
+            return Ok((404, error_body(&e)));
+        }
+    };
+    Ok((200, body))
+}
+
+fn read_body(request: &mut Request) -> Result<Vec<u8>, CliError> {
+    let mut body = Vec::new();
+    request
+        .as_reader()
+        .take(MAX_BODY_BYTES + 1)
+        .read_to_end(&mut body)
+        .map_err(|e| CliError::io_error(format!("reading request body: {e}")))?;
+    if body.len() as u64 > MAX_BODY_BYTES {
+        return Err(CliError::new(
+            USAGE_ERROR,
+            format!("request body exceeds {MAX_BODY_BYTES} bytes"),
+        ));
+    }
+    Ok(body)
+}
+
+/// `POST /resolve` with `{"query", "budget", "format"?, "explain"?}`, as
+/// `context resolve --query Q --budget N [--format F] [--explain]`.
+fn resolve(resolver: &Resolver, body: &[u8]) -> Result<Vec<u8>, CliError> {
+    let request: Value = serde_json::from_slice(body)
+        .map_err(|e| CliError::new(USAGE_ERROR, format!("invalid JSON body: {e}")))?;
+    let fields = request
+        .as_object()
+        .ok_or_else(|| CliError::new(USAGE_ERROR, "request body must be a JSON object"))?;
+    if let Some(key) = fields.keys().find(|key| !RESOLVE_FIELDS.contains(&key.as_str())) {
+        return Err(CliError::new(USAGE_ERROR, format!("unknown field {key:?}")));
+    }
+
+    let query = request["query"]
+        .as_str()
+        .ok_or_else(|| CliError::new(INVALID_QUERY, "missing \"query\" string"))?;
+    let query = NormalizedQuery::parse(query)?;
+    let budget = request["budget"]
+        .as_u64()
+        .and_then(|b| usize::try_from(b).ok())
+        .ok_or_else(|| {
+            CliError::new(INVALID_BUDGET, "\"budget\" must be a non-negative integer")
+        })?;
+    let format = match request["format"].as_str() {
+        None | Some("json") => Format::Json,
+        Some("pretty") => Format::Pretty,
+        Some(other) => {
+            return Err(CliError::new(
+                USAGE_ERROR,
+                format!("unsupported format {other:?} (expected json or pretty)"),
+            ))
+        }
+    };
+
+    if request["explain"].as_bool().unwrap_or(false) {
+        return resolve::json_bytes(&resolver.explain(&query, budget)?, format);
+    }
+    resolve::json_bytes(&resolver.select(&query, budget)?, format)
+}
+
+/// HTTP status for an error, by its frozen exit code.
+fn status_for(e: &CliError) -> u16 {
+    match e.code {
+        USAGE_ERROR | INVALID_QUERY | INVALID_BUDGET => 400,
+        CACHE_MISSING => 404,
+        _ => 500,
+    }
+}
+
+fn error_body(e: &CliError) -> Vec<u8> {
+    resolve::json_bytes(&e.to_json(), Format::Json).unwrap_or_default()
+}
@@ -0,0 +1,159 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::chunking::ChunkBy;
+use crate::commands::inspect::SortKey;
+use crate::commands::resolve::Format;
+use crate::exit_codes::{CliError, USAGE_ERROR};
+use crate::filters::MetadataFilter;
+use crate::formats::SourceFormat;
+use crate::frontmatter::UnknownKeys;
+use crate::render::HeaderField;
+
+/// Project configuration file, discovered from the current directory upward.
+pub const CONFIG_FILENAME: &str = "context.toml";
+
+/// Defaults for command arguments, read from `context.toml`.
+///
+/// Every field is optional; flags given on the command line take precedence.
+/// Relative paths are resolved against the directory containing the file.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Cache directory shared by all commands unless a section overrides it.
+    pub cache: Option<PathBuf>,
+    pub build: BuildConfig,
+    pub resolve: ResolveConfig,
+    pub inspect: InspectConfig,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct BuildConfig {
+    pub sources: Option<PathBuf>,
+    pub cache: Option<PathBuf>,
+    pub force: Option<bool>,
+    pub incremental: Option<bool>,
+    pub unknown_metadata: Option<UnknownKeys>,
+    pub include: Option<Vec<String>>,
+    pub exclude: Option<Vec<String>>,
+    pub formats: Option<Vec<SourceFormat>>,
+    pub chunk_by: Option<ChunkBy>,
+    pub max_chunk_tokens: Option<usize>,
+    pub debounce: Option<u64>,
+    pub queries: Option<PathBuf>,
+    pub keep_going: Option<bool>,
+    pub report: Option<PathBuf>,
+    pub max_file_bytes: Option<u64>,
+    pub max_failed: Option<usize>,
+    pub max_warnings: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ResolveConfig {
+    pub cache: Option<PathBuf>,
+    pub query: Option<String>,
+    pub budget: Option<usize>,
+    pub format: Option<Format>,
+    pub filters: Option<Vec<MetadataFilter>>,
+    pub path_prefixes: Option<Vec<String>>,
+    pub exclude_ids: Option<Vec<String>>,
+    /// Document IDs included in every resolve.
+    pub pins: Option<Vec<String>>,
+    pub header: Option<Vec<HeaderField>>,
+    pub separator: Option<String>,
+    pub template: Option<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct InspectConfig {
+    pub cache: Option<PathBuf>,
+    pub deep: Option<bool>,
+    pub sort: Option<SortKey>,
+    pub prefix: Option<String>,
+}
+
+/// Configuration together with the file it was read from, if any.
+#[derive(Default)]
+pub struct LoadedConfig {
+    pub path: Option<PathBuf>,
+    pub config: Config,
+}
+
+/// Load `explicit`, or the nearest `context.toml` from the current directory
+/// upward. No file found means an empty configuration.
+pub fn load(explicit: Option<&Path>) -> Result<LoadedConfig, CliError> {
+    let path = match explicit {
+        Some(path) => path.to_path_buf(),
+        None => match discover() {
+            Some(path) => path,
+            None => return Ok(LoadedConfig::default()),
+        },
+    };
+
+    let text = std::fs::read_to_string(&path)
+        .map_err(|e| CliError::io_error(format!("reading {}: {e}", path.display())))?;
+    let mut config: Config = toml::from_str(&text).map_err(|e| {
+        CliError::new(USAGE_ERROR, format!("invalid config {}: {e}", path.display()))
+    })?;
+
+    let base = path.parent().unwrap_or(Path::new("."));
+    config.resolve_paths(base);
+
+    Ok(LoadedConfig {
+        path: Some(path),
+        config,
+    })
+}
+
+fn discover() -> Option<PathBuf> {
+    let cwd = std::env::current_dir().ok()?;
+    cwd.ancestors()
+        .map(|dir| dir.join(CONFIG_FILENAME))
+        .find(|candidate| candidate.is_file())
+}
+
+impl Config {
+    fn resolve_paths(&mut self, base: &Path) {
+        let paths = [
+            &mut self.cache,
+            &mut self.build.sources,
+            &mut self.build.cache,
+            &mut self.build.queries,
+            &mut self.build.report,
+            &mut self.resolve.cache,
+            &mut self.resolve.template,
+            &mut self.inspect.cache,
+        ];
+        for path in paths.into_iter().flatten() {
+            if path.is_relative() {
+                *path = base.join(&*path);
+            }
+        }
+    }
+
+    pub fn build_cache(&self) -> Option<PathBuf> {
+        self.build.cache.clone().or_else(|| self.cache.clone())
+    }
+
+    pub fn resolve_cache(&self) -> Option<PathBuf> {
+        self.resolve.cache.clone().or_else(|| self.cache.clone())
+    }
+
+    pub fn inspect_cache(&self) -> Option<PathBuf> {
+        self.inspect.cache.clone().or_else(|| self.cache.clone())
+    }
+}
+
+/// Unwrap an argument that may come from the command line or `context.toml`.
+pub fn required<T>(value: Option<T>, flag: &str) -> Result<T, CliError> {
+    value.ok_or_else(|| {
+        CliError::new(
+            USAGE_ERROR,
+            format!("missing {flag} (pass it on the command line or set it in {CONFIG_FILENAME})"),
+        )
+    })
+}
@@ -11,6 +11,13 @@ pub const CACHE_INVALID: i32 = 5;
 pub const IO_ERROR: i32 = 6;
 pub const INTERNAL_ERROR: i32 = 7;
 
+// Returned by comparison commands run with --exit-code when their inputs
+// differ. Shares the value of USAGE_ERROR, as diff(1) and `git diff` do.
+pub const DIFFERS: i32 = 1;
+
+// Returned when a quality gate such as `eval --min-recall` is not met.
+pub const THRESHOLD_FAILED: i32 = 1;
+
 pub struct CliError {
     pub code: i32,
     pub message: String,
@@ -32,6 +39,20 @@ impl CliError {
         Self::new(CACHE_INVALID, format!("Cache is invalid: {detail}"))
     }
 
+    /// A lookup by document ID or source path matched nothing. Exit codes
+    /// are frozen, so this is a usage error with its own message.
+    pub fn document_not_found(detail: impl fmt::Display) -> Self {
+        Self::new(USAGE_ERROR, format!("Document not found in cache: {detail}"))
+    }
+
+    pub fn differs(detail: impl fmt::Display) -> Self {
+        Self::new(DIFFERS, detail.to_string())
+    }
+
+    pub fn threshold_failed(detail: impl fmt::Display) -> Self {
+        Self::new(THRESHOLD_FAILED, detail.to_string())
+    }
+
     pub fn io_error(detail: impl fmt::Display) -> Self {
         Self::new(IO_ERROR, format!("I/O error: {detail}"))
     }
@@ -40,6 +61,31 @@ impl CliError {
         Self::new(INTERNAL_ERROR, format!("Internal error: {detail}"))
     }
 
+    /// The error code shared with the MCP error schema.
+    pub fn name(&self) -> &'static str {
+        match self.code {
+            INVALID_QUERY => "invalid_query",
+            INVALID_BUDGET => "invalid_budget",
+            CACHE_MISSING => "cache_missing",
+            CACHE_INVALID => "cache_invalid",
+            IO_ERROR => "io_error",
+            INTERNAL_ERROR => "internal_error",
+            _ => "usage_error",
+        }
+    }
+
+    /// Error body for the server modes: `{"error": {"code", "exit_code",
+    /// "message"}}`.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "error": {
+                "code": self.name(),
+                "exit_code": self.code,
+                "message": self.message,
+            }
+        })
+    }
+
     pub fn exit(self) -> ! {
         eprintln!("error: {}", self.message);
         process::exit(self.code);
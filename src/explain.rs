@@ -0,0 +1,139 @@
+use serde_json::{json, Value};
+
+use context_core::cache::ContextCache;
+use context_core::selection::ContextSelector;
+use context_core::types::Query;
+
+use crate::cache;
+use crate::exit_codes::CliError;
+
+/// Budget large enough that every scored document fits.
+const UNBOUNDED_BUDGET: usize = usize::MAX;
+
+/// Explain a selection: for every document in the cache, its score, token
+/// count, rank, and why it was included or excluded.
+///
+/// `result` is the serialized resolve result for `query` at `budget`.
+/// Candidate ranks and scores come from re-running the selector with an
+/// unbounded budget, so the ranking is exactly the one the selector used.
+/// Reasons are:
+///
+/// - `within_budget`: selected.
+/// - `exceeds_remaining_budget`: scored, but did not fit what was left.
+/// - `tie_broken`: scored equal to a candidate with the opposite outcome,
+///   where the excluded one would have fitted the budget remaining at the
+///   included one's rank, so the selector's tie-break decided it.
+/// - `below_threshold`: not scored by the selector for this query.
+/// - `pinned`: included ahead of selection by `--pin`.
+pub fn explain(
+    cache: &ContextCache,
+    query: &str,
+    budget: usize,
+    result: &Value,
+) -> Result<Value, CliError> {
+    let selector = ContextSelector::default();
+    let ranked = selector.select(cache, Query::new(query), UNBOUNDED_BUDGET)?;
+    let ranked = serde_json::to_value(&ranked).map_err(|e| CliError::internal(e))?;
+    let ranked = ranked["documents"].as_array().cloned().unwrap_or_default();
+
+    let selected: Vec<&str> = result["documents"]
+        .as_array()
+        .map(|docs| docs.iter().filter_map(|doc| doc["id"].as_str()).collect())
+        .unwrap_or_default();
+
+    // Pinned documents are included ahead of selection
+    let mut candidates = Vec::new();
+    let mut used = 0u64;
+    let pinned = result["documents"].as_array().into_iter().flatten();
+    for doc in pinned.filter(|doc| doc["pinned"] == true) {
+        let tokens = doc["tokens"].as_u64().unwrap_or(0);
+        candidates.push(json!({
+            "id": doc["id"],
+            "rank": null,
+            "score": null,
+            "tokens": tokens,
+            "included": true,
+            "remaining_budget": (budget as u64).saturating_sub(used),
+            "reason": "pinned",
+        }));
+        used += tokens;
+    }
+    let pinned_count = candidates.len();
+
+    // Scored candidates, in rank order
+    for (index, doc) in ranked.iter().enumerate() {
+        let id = doc["id"].as_str().unwrap_or_default();
+        let tokens = doc["tokens"].as_u64().unwrap_or(0);
+        let included = selected.contains(&id);
+        let remaining = (budget as u64).saturating_sub(used);
+        if included {
+            used += tokens;
+        }
+        candidates.push(json!({
+            "id": id,
+            "rank": index + 1,
+            "score": doc.get("score").cloned().unwrap_or(Value::Null),
+            "tokens": tokens,
+            "included": included,
+            "remaining_budget": remaining,
+        }));
+    }
+
+    for index in pinned_count..candidates.len() {
+        let candidate = &candidates[index];
+        let tied = candidates[pinned_count..].iter().any(|other| {
+            if other["id"] == candidate["id"]
+                || other["score"] != candidate["score"]
+                || other["included"] == candidate["included"]
+            {
+                return false;
+            }
+            // Otherwise the budget, not the tie-break, decided between them
+            let (included, excluded) = if candidate["included"] == true {
+                (candidate, other)
+            } else {
+                (other, candidate)
+            };
+            excluded["tokens"].as_u64().unwrap_or(0)
+                <= included["remaining_budget"].as_u64().unwrap_or(0)
+        });
+        let reason = match (candidates[index]["included"].as_bool(), tied) {
+            (_, true) => "tie_broken",
+            (Some(true), false) => "within_budget",
+            _ => "exceeds_remaining_budget",
+        };
+        candidates[index]["reason"] = json!(reason);
+    }
+
+    // Documents the selector did not score at all
+    let scored: Vec<String> = candidates
+        .iter()
+        .filter_map(|c| c["id"].as_str().map(str::to_string))
+        .collect();
+    let mut unscored = Vec::new();
+    for entry in &cache.manifest.documents {
+        let id = entry.id.to_string();
+        if scored.contains(&id) {
+            continue;
+        }
+        unscored.push(json!({
+            "id": id,
+            "rank": null,
+            "score": null,
+            "tokens": cache::document_tokens(&cache.root, &entry.file)?,
+            "included": false,
+            "reason": "below_threshold",
+        }));
+    }
+    unscored.sort_by(|a, b| a["id"].as_str().cmp(&b["id"].as_str()));
+    candidates.extend(unscored);
+
+    Ok(json!({
+        "query": query,
+        "budget": budget,
+        "tokens_used": used,
+        "candidate_count": candidates.len(),
+        "selected_count": selected.len(),
+        "candidates": candidates,
+    }))
+}
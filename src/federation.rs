@@ -0,0 +1,194 @@
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::Serialize;
+use tempfile::TempDir;
+
+use context_core::cache::{CacheBuildConfig, CacheBuilder, ContextCache};
+use context_core::document::{Document, DocumentId};
+
+use crate::cache;
+use crate::exit_codes::{CliError, USAGE_ERROR};
+
+/// A `--cache` argument: a cache directory with an optional alias, written
+/// `ALIAS=DIR`.
+#[derive(Clone)]
+pub struct CacheSpec {
+    pub alias: Option<String>,
+    pub path: PathBuf,
+}
+
+impl FromStr for CacheSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((alias, path)) = s.split_once('=') {
+            if is_alias(alias) && !path.is_empty() {
+                return Ok(CacheSpec {
+                    alias: Some(alias.to_string()),
+                    path: PathBuf::from(path),
+                });
+            }
+        }
+        Ok(CacheSpec::from(PathBuf::from(s)))
+    }
+}
+
+impl From<PathBuf> for CacheSpec {
+    fn from(path: PathBuf) -> Self {
+        CacheSpec { alias: None, path }
+    }
+}
+
+fn is_alias(alias: &str) -> bool {
+    !alias.is_empty()
+        && alias
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+struct Member {
+    alias: String,
+    path: PathBuf,
+    cache_version: String,
+}
+
+/// One or more caches loaded as a single candidate pool.
+///
+/// A single cache is used as-is. Several caches are merged into one cache
+/// in which every document ID is prefixed with its cache's alias
+/// (`alias/id`), so selection ranks all documents together under one
+/// budget. The merged cache lives in a private temporary directory that
+/// is removed when the federation is dropped.
+pub struct Federation {
+    pub cache: ContextCache,
+    members: Vec<Member>,
+    _scratch: Option<TempDir>,
+}
+
+impl Federation {
+    pub fn load(specs: &[CacheSpec]) -> Result<Self, CliError> {
+        if let [spec] = specs {
+            return Ok(Federation {
+                cache: cache::load_cache(&spec.path)?,
+                members: Vec::new(),
+                _scratch: None,
+            });
+        }
+
+        let mut members = Vec::with_capacity(specs.len());
+        let mut loaded = Vec::with_capacity(specs.len());
+        for spec in specs {
+            let alias = match &spec.alias {
+                Some(alias) => alias.clone(),
+                None => spec
+                    .path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .filter(|name| is_alias(name))
+                    .ok_or_else(|| {
+                        CliError::new(
+                            USAGE_ERROR,
+                            format!(
+                                "cannot derive an alias from {}; pass ALIAS={}",
+                                spec.path.display(),
+                                spec.path.display()
+                            ),
+                        )
+                    })?,
+            };
+            if members.iter().any(|m: &Member| m.alias == alias) {
+                return Err(CliError::new(
+                    USAGE_ERROR,
+                    format!("duplicate cache alias {alias:?}; pass ALIAS=DIR to disambiguate"),
+                ));
+            }
+
+            let cache = cache::load_cache(&spec.path)?;
+            members.push(Member {
+                alias,
+                path: spec.path.clone(),
+                cache_version: cache.manifest.cache_version.to_string(),
+            });
+            loaded.push(cache);
+        }
+
+        // Owner-only and uniquely named, so no other user can plant or read
+        // the merged documents
+        let scratch = tempfile::Builder::new()
+            .prefix("context-federated-")
+            .tempdir()
+            .map_err(|e| CliError::io_error(format!("creating merged cache: {e}")))?;
+        let cache = merge(&members, &loaded, &scratch.path().join("cache"))?;
+        Ok(Federation {
+            cache,
+            members,
+            _scratch: Some(scratch),
+        })
+    }
+
+    /// Alias of the cache a selected document came from, or `None` for a
+    /// single cache.
+    pub fn alias_of(&self, id: &str) -> Option<String> {
+        if self.members.is_empty() {
+            return None;
+        }
+        id.split_once('/').map(|(alias, _)| alias.to_string())
+    }
+
+    /// The merged caches to record in a result's `selection` block, or
+    /// `None` for a single cache.
+    pub fn summary(&self) -> Option<Vec<CacheSummary>> {
+        if self.members.is_empty() {
+            return None;
+        }
+        let caches = self
+            .members
+            .iter()
+            .map(|m| CacheSummary {
+                alias: m.alias.clone(),
+                path: m.path.clone(),
+                cache_version: m.cache_version.clone(),
+            })
+            .collect();
+        Some(caches)
+    }
+}
+
+/// One entry of `selection.caches` in a resolve result.
+#[derive(Serialize)]
+pub struct CacheSummary {
+    pub alias: String,
+    pub path: PathBuf,
+    pub cache_version: String,
+}
+
+/// Build the merged cache of `members` at `merged`.
+fn merge(
+    members: &[Member],
+    loaded: &[ContextCache],
+    merged: &Path,
+) -> Result<ContextCache, CliError> {
+    let mut documents = Vec::new();
+    for (member, cache) in members.iter().zip(loaded) {
+        for entry in &cache.manifest.documents {
+            documents.push(prefixed(cache, &entry.file, &member.alias)?);
+        }
+    }
+    Ok(CacheBuilder::new(CacheBuildConfig::v0()).build(documents, merged)?)
+}
+
+/// A stored document, reused as-is apart from its ID, which is prefixed
+/// by `alias`.
+fn prefixed(cache: &ContextCache, file: &str, alias: &str) -> Result<Document, CliError> {
+    let mut stored = cache::read_document(&cache.root, file)?;
+    let invalid = |e: &dyn std::fmt::Display| CliError::cache_invalid(format!("{file}: {e}"));
+
+    // Derived as if the document lived under an `alias/` directory
+    let id = stored["id"].as_str().ok_or_else(|| invalid(&"missing id"))?;
+    let id = DocumentId::from_path(Path::new(""), &Path::new(alias).join(id))
+        .map_err(|e| invalid(&e))?;
+    stored["id"] = serde_json::Value::String(id.to_string());
+
+    serde_json::from_value(stored).map_err(|e| invalid(&e))
+}
@@ -0,0 +1,150 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use context_core::cache::ContextCache;
+
+use crate::cache;
+use crate::exit_codes::CliError;
+
+/// A `--filter` condition on one metadata field.
+///
+/// `key=value` keeps documents whose field equals `value`, or contains it
+/// when the field is a list. `key!=value` keeps all other documents,
+/// including those without the field.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+pub struct MetadataFilter {
+    pub key: String,
+    pub value: String,
+    pub negate: bool,
+}
+
+impl FromStr for MetadataFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, value, negate) = match s.split_once("!=") {
+            Some((key, value)) => (key, value, true),
+            None => match s.split_once('=') {
+                Some((key, value)) => (key, value, false),
+                None => return Err(format!("expected key=value or key!=value, got {s:?}")),
+            },
+        };
+        if key.is_empty() {
+            return Err(format!("missing metadata key in {s:?}"));
+        }
+        Ok(MetadataFilter {
+            key: key.to_string(),
+            value: value.to_string(),
+            negate,
+        })
+    }
+}
+
+impl TryFrom<String> for MetadataFilter {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<MetadataFilter> for String {
+    fn from(filter: MetadataFilter) -> Self {
+        filter.to_string()
+    }
+}
+
+impl std::fmt::Display for MetadataFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let op = if self.negate { "!=" } else { "=" };
+        write!(f, "{}{op}{}", self.key, self.value)
+    }
+}
+
+impl MetadataFilter {
+    fn matches(&self, metadata: &Value) -> bool {
+        let equals = |v: &Value| match v {
+            Value::String(s) => *s == self.value,
+            Value::Null => false,
+            other => other.to_string() == self.value,
+        };
+        let found = match &metadata[self.key.as_str()] {
+            Value::Array(items) => items.iter().any(equals),
+            value => equals(value),
+        };
+        found != self.negate
+    }
+}
+
+/// Restrictions on the candidate documents of a resolve.
+#[derive(Clone, Default)]
+pub struct DocumentFilter {
+    pub metadata: Vec<MetadataFilter>,
+    /// Source path prefixes; a document must match at least one.
+    pub path_prefixes: Vec<String>,
+    pub exclude_ids: Vec<String>,
+}
+
+impl DocumentFilter {
+    pub fn is_empty(&self) -> bool {
+        self.metadata.is_empty() && self.path_prefixes.is_empty() && self.exclude_ids.is_empty()
+    }
+
+    /// Remove documents that do not pass the filter from the cache's
+    /// manifest, so selection only sees the remaining candidates.
+    pub fn apply(&self, cache: &mut ContextCache) -> Result<(), CliError> {
+        if self.is_empty() {
+            return Ok(());
+        }
+
+        let mut keep = Vec::with_capacity(cache.manifest.documents.len());
+        for entry in &cache.manifest.documents {
+            if self.exclude_ids.contains(&entry.id.to_string()) {
+                keep.push(false);
+                continue;
+            }
+            if self.metadata.is_empty() && self.path_prefixes.is_empty() {
+                keep.push(true);
+                continue;
+            }
+
+            let stored = cache::read_document(&cache.root, &entry.file)?;
+            let source = stored["source"].as_str().unwrap_or_default().replace('\\', "/");
+            let in_prefix = self.path_prefixes.is_empty()
+                || self.path_prefixes.iter().any(|prefix| source.starts_with(prefix.as_str()));
+            let metadata_matches = self.metadata.iter().all(|f| f.matches(&stored["metadata"]));
+            keep.push(in_prefix && metadata_matches);
+        }
+
+        let mut keep = keep.into_iter();
+        cache
+            .manifest
+            .documents
+            .retain(|_| keep.next().unwrap_or(false));
+        Ok(())
+    }
+
+    /// The filters to record in a result's `selection` block, or `None` for
+    /// an empty filter.
+    pub fn summary(&self) -> Option<FilterSummary> {
+        if self.is_empty() {
+            return None;
+        }
+        Some(FilterSummary {
+            metadata: self.metadata.iter().map(|f| f.to_string()).collect(),
+            path_prefixes: self.path_prefixes.clone(),
+            exclude_ids: self.exclude_ids.clone(),
+        })
+    }
+}
+
+/// `selection.filters` of a resolve result.
+#[derive(Serialize)]
+pub struct FilterSummary {
+    pub metadata: Vec<String>,
+    pub path_prefixes: Vec<String>,
+    pub exclude_ids: Vec<String>,
+}
@@ -0,0 +1,344 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// Source formats the build can ingest, selected with `--formats`.
+///
+/// Every format is normalized to markdown-ish plain text before ingestion.
+/// Normalizers are pure functions of the input bytes, so each format is as
+/// deterministic as markdown itself.
+#[derive(ValueEnum, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceFormat {
+    Md,
+    Markdown,
+    Mdx,
+    Txt,
+    Rst,
+    Adoc,
+    Html,
+}
+
+impl SourceFormat {
+    /// Map a file extension (case-insensitive) to its format.
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_ascii_lowercase().as_str() {
+            "md" => Some(Self::Md),
+            "markdown" => Some(Self::Markdown),
+            "mdx" => Some(Self::Mdx),
+            "txt" => Some(Self::Txt),
+            "rst" => Some(Self::Rst),
+            "adoc" | "asciidoc" => Some(Self::Adoc),
+            "html" | "htm" => Some(Self::Html),
+            _ => None,
+        }
+    }
+
+    /// Convert raw file content to the text passed to `Document::ingest`.
+    ///
+    /// Content that is not valid UTF-8 is returned unchanged so ingestion
+    /// reports it exactly as it would for markdown.
+    pub fn normalize(self, raw: Vec<u8>) -> Vec<u8> {
+        let convert: fn(&str) -> String = match self {
+            Self::Md | Self::Markdown | Self::Txt => return raw,
+            Self::Mdx => mdx_to_markdown,
+            Self::Rst => rst_to_markdown,
+            Self::Adoc => adoc_to_markdown,
+            Self::Html => html_to_text,
+        };
+        match std::str::from_utf8(&raw) {
+            Ok(text) => convert(text).into_bytes(),
+            Err(_) => raw,
+        }
+    }
+}
+
+/// Join lines with `\n`, collapsing runs of blank lines and trimming blank
+/// lines at either end.
+fn join_lines<I: IntoIterator<Item = String>>(lines: I) -> String {
+    let mut out = String::new();
+    let mut blank_run = false;
+    for line in lines {
+        let line = line.trim_end();
+        if line.is_empty() {
+            blank_run = !out.is_empty();
+            continue;
+        }
+        if blank_run {
+            out.push('\n');
+            blank_run = false;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+/// Drop top-level ESM `import`/`export` lines outside code fences.
+fn mdx_to_markdown(text: &str) -> String {
+    let mut in_fence = false;
+    let mut lines = Vec::new();
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        } else if !in_fence && (line.starts_with("import ") || line.starts_with("export ")) {
+            continue;
+        }
+        lines.push(line.to_string());
+    }
+    join_lines(lines)
+}
+
+/// Characters reStructuredText accepts as section adornment.
+const RST_ADORNMENT: &str = "=-~^\"'`#*+_:.<>";
+
+/// Return the adornment character if `line` is a section adornment line.
+fn rst_adornment(line: &str) -> Option<char> {
+    let line = line.trim_end();
+    let first = line.chars().next()?;
+    if line.len() < 2 || !RST_ADORNMENT.contains(first) || !line.chars().all(|c| c == first) {
+        return None;
+    }
+    Some(first)
+}
+
+/// Heading level for an adornment style, assigned in order of first use.
+fn rst_level(styles: &mut Vec<(char, bool)>, style: (char, bool)) -> usize {
+    let index = match styles.iter().position(|s| *s == style) {
+        Some(index) => index,
+        None => {
+            styles.push(style);
+            styles.len() - 1
+        }
+    };
+    (index + 1).min(6)
+}
+
+/// Convert section titles to `#` headings and drop comments and directive
+/// markers, keeping directive bodies as plain text.
+fn rst_to_markdown(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut styles = Vec::new();
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+
+        // Title with overline and underline
+        if let Some(c) = rst_adornment(line) {
+            if i + 2 < lines.len()
+                && !lines[i + 1].trim().is_empty()
+                && rst_adornment(lines[i + 2]) == Some(c)
+            {
+                let level = rst_level(&mut styles, (c, true));
+                out.push(format!("{} {}", "#".repeat(level), lines[i + 1].trim()));
+                i += 3;
+                continue;
+            }
+        }
+
+        // Title with underline only
+        if !line.trim().is_empty() && !line.starts_with(' ') && rst_adornment(line).is_none() {
+            if let Some(c) = lines.get(i + 1).and_then(|next| rst_adornment(next)) {
+                if lines[i + 1].trim_end().chars().count() >= line.trim_end().chars().count() {
+                    let level = rst_level(&mut styles, (c, false));
+                    out.push(format!("{} {}", "#".repeat(level), line.trim()));
+                    i += 2;
+                    continue;
+                }
+            }
+        }
+
+        if line.starts_with(".. ") || line.trim_end() == ".." {
+            i += 1;
+            continue;
+        }
+
+        match line.strip_suffix("::") {
+            Some(stripped) if !stripped.trim().is_empty() => out.push(format!("{stripped}:")),
+            _ => out.push(line.to_string()),
+        }
+        i += 1;
+    }
+    join_lines(out)
+}
+
+/// Convert `=` section titles to `#` headings and listing/literal
+/// delimiters to code fences.
+fn adoc_to_markdown(text: &str) -> String {
+    let lines = text.lines().map(|line| {
+        let trimmed = line.trim_end();
+        let depth = trimmed.chars().take_while(|&c| c == '=').count();
+        if depth > 0 && depth <= 6 && trimmed[depth..].starts_with(' ') {
+            return format!("{}{}", "#".repeat(depth), &trimmed[depth..]);
+        }
+        let delimiter = trimmed.len() >= 4
+            && (trimmed.chars().all(|c| c == '-') || trimmed.chars().all(|c| c == '.'));
+        if delimiter {
+            return "```".to_string();
+        }
+        line.to_string()
+    });
+    join_lines(lines)
+}
+
+/// Elements rendered as a line break in extracted HTML text.
+const HTML_BLOCK_TAGS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "br",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "footer",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "td",
+    "th",
+    "tr",
+    "ul",
+];
+
+/// Extract readable text from HTML: drop tags, comments, scripts and
+/// styles, render headings and list items as markdown, and decode common
+/// character references.
+fn html_to_text(html: &str) -> String {
+    let mut out = String::new();
+    let mut in_pre = false;
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        let Some(start) = rest.find('<') else {
+            push_text(&mut out, rest, in_pre);
+            break;
+        };
+        push_text(&mut out, &rest[..start], in_pre);
+        rest = &rest[start..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+
+        let Some(end) = rest.find('>') else {
+            push_text(&mut out, rest, in_pre);
+            break;
+        };
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+
+        let closing = tag.starts_with('/');
+        let name: String = tag
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_ascii_lowercase();
+
+        if !closing && (name == "script" || name == "style") {
+            let close = format!("</{name}");
+            rest = rest
+                .to_ascii_lowercase()
+                .find(&close)
+                .map_or("", |i| &rest[i..]);
+            continue;
+        }
+
+        if name == "pre" {
+            in_pre = !closing;
+        }
+
+        let heading = name.len() == 2
+            && name.starts_with('h')
+            && matches!(name.as_bytes()[1], b'1'..=b'6');
+        if heading {
+            out.push('\n');
+            if !closing {
+                let level = (name.as_bytes()[1] - b'0') as usize;
+                out.push_str(&"#".repeat(level));
+                out.push(' ');
+            }
+        } else if name == "li" && !closing {
+            out.push_str("\n- ");
+        } else if HTML_BLOCK_TAGS.contains(&name.as_str()) {
+            out.push('\n');
+        }
+    }
+
+    join_lines(out.lines().map(str::to_string))
+}
+
+/// Append decoded text, collapsing whitespace outside `<pre>`.
+fn push_text(out: &mut String, text: &str, in_pre: bool) {
+    let text = decode_entities(text);
+    if in_pre {
+        out.push_str(&text);
+        return;
+    }
+    for (i, word) in text.split_whitespace().enumerate() {
+        if i > 0 || (text.starts_with(char::is_whitespace) && !at_break(out)) {
+            out.push(' ');
+        }
+        out.push_str(word);
+    }
+    if text.ends_with(char::is_whitespace) && !at_break(out) {
+        out.push(' ');
+    }
+}
+
+/// Whether `out` is empty or already ends in a space or line break.
+fn at_break(out: &str) -> bool {
+    out.is_empty() || out.ends_with('\n') || out.ends_with(' ')
+}
+
+/// Decode named and numeric character references used in typical docs.
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            c.map(|c| (c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
@@ -0,0 +1,100 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Front-matter keys mapped into document metadata regardless of policy.
+pub const KNOWN_KEYS: &[&str] = &["audience", "owner", "tags", "title", "version"];
+
+/// Policy for front-matter keys not listed in [`KNOWN_KEYS`].
+#[derive(ValueEnum, Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum UnknownKeys {
+    #[default]
+    Keep,
+    Drop,
+    Reject,
+}
+
+/// Front matter split from the body of a source document.
+pub struct FrontMatter {
+    /// Metadata fields, sorted by key.
+    pub fields: Map<String, Value>,
+    /// Document content with the front-matter block removed.
+    pub body: Vec<u8>,
+}
+
+/// Split leading `---` YAML or `+++` TOML front matter from `raw`.
+///
+/// Content without a front-matter block (including non-UTF-8 content) is
+/// returned unchanged with no fields.
+pub fn split(raw: Vec<u8>, unknown: UnknownKeys) -> Result<FrontMatter, String> {
+    let Some((kind, header, body_start)) = std::str::from_utf8(&raw)
+        .ok()
+        .and_then(locate)
+    else {
+        return Ok(FrontMatter {
+            fields: Map::new(),
+            body: raw,
+        });
+    };
+
+    let value: Value = match kind {
+        Kind::Yaml => serde_yaml::from_str(header).map_err(|e| format!("invalid YAML: {e}"))?,
+        Kind::Toml => toml::from_str(header).map_err(|e| format!("invalid TOML: {e}"))?,
+    };
+
+    let mut fields = match value {
+        Value::Object(map) => map,
+        Value::Null => Map::new(),
+        _ => return Err("front matter must be a key/value mapping".to_string()),
+    };
+
+    match unknown {
+        UnknownKeys::Keep => {}
+        UnknownKeys::Drop => fields.retain(|k, _| KNOWN_KEYS.contains(&k.as_str())),
+        UnknownKeys::Reject => {
+            if let Some(key) = fields.keys().find(|k| !KNOWN_KEYS.contains(&k.as_str())) {
+                return Err(format!("unknown front-matter key: {key}"));
+            }
+        }
+    }
+
+    Ok(FrontMatter {
+        fields,
+        body: raw[body_start..].to_vec(),
+    })
+}
+
+enum Kind {
+    Yaml,
+    Toml,
+}
+
+/// Find the front-matter block, returning its kind, its inner text, and the
+/// byte offset where the body begins.
+fn locate(text: &str) -> Option<(Kind, &str, usize)> {
+    let (kind, fence) = if text.starts_with("---") {
+        (Kind::Yaml, "---")
+    } else if text.starts_with("+++") {
+        (Kind::Toml, "+++")
+    } else {
+        return None;
+    };
+
+    let mut lines = text.split_inclusive('\n');
+    let first = lines.next()?;
+    if first.trim_end() != fence {
+        return None;
+    }
+
+    let header_start = first.len();
+    let mut offset = header_start;
+    for line in lines {
+        let trimmed = line.trim_end();
+        if trimmed == fence || (matches!(kind, Kind::Yaml) && trimmed == "...") {
+            return Some((kind, &text[header_start..offset], offset + line.len()));
+        }
+        offset += line.len();
+    }
+    None
+}
@@ -1,11 +1,31 @@
+mod build_report;
+mod cache;
+mod chunking;
 mod commands;
+mod config;
 mod exit_codes;
+mod explain;
+mod federation;
+mod filters;
+mod formats;
+mod frontmatter;
+mod pins;
+mod queries;
+mod render;
+mod sources;
+mod verify;
+
+use std::path::PathBuf;
 
 use clap::{Parser, Subcommand};
 
 #[derive(Parser)]
 #[command(name = "context", version, about = "Context platform CLI")]
 struct Cli {
+    /// Path to a context.toml (default: nearest one in the current directory or its parents)
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -18,15 +38,62 @@ enum Commands {
     Resolve(commands::resolve::ResolveArgs),
     /// Inspect cache state and metadata
     Inspect(commands::inspect::InspectArgs),
+    /// List cached documents (same as `inspect --documents`)
+    Ls(commands::inspect::ListArgs),
+    /// Print a cached document by ID or source path
+    Cat(commands::cat::CatArgs),
+    /// Compare the documents of two caches
+    Diff(commands::diff::DiffArgs),
+    /// Compare what two caches select for the same queries
+    DiffResolve(commands::diff_resolve::DiffResolveArgs),
+    /// Measure selection quality against labeled judgments
+    Eval(commands::eval::EvalArgs),
+    /// Serve resolve and inspect over HTTP from a cache loaded once
+    Serve(commands::serve::ServeArgs),
+    /// Serve the context.resolve tool over MCP on stdin/stdout
+    Mcp(commands::mcp::McpArgs),
+    /// Show project configuration
+    #[command(subcommand)]
+    Config(commands::config::ConfigCommand),
 }
 
 fn main() {
     let cli = Cli::parse();
 
+    // Only commands that take defaults from context.toml read it, so a
+    // stray or foreign config cannot break the others
+    let load = || config::load(cli.config.as_deref());
     let result = match cli.command {
-        Commands::Build(args) => commands::build::run(args),
-        Commands::Resolve(args) => commands::resolve::run(args),
-        Commands::Inspect(args) => commands::inspect::run(args),
+        Commands::Build(args) => {
+            load().and_then(|loaded| commands::build::run(args.with_config(&loaded.config)))
+        }
+        Commands::Resolve(args) => {
+            load().and_then(|loaded| commands::resolve::run(args.with_config(&loaded.config)))
+        }
+        Commands::Inspect(args) => {
+            load().and_then(|loaded| commands::inspect::run(args.with_config(&loaded.config)))
+        }
+        Commands::Ls(args) => load().and_then(|loaded| {
+            let args = commands::inspect::InspectArgs::from(args);
+            commands::inspect::run(args.with_config(&loaded.config))
+        }),
+        Commands::Cat(args) => {
+            load().and_then(|loaded| commands::cat::run(args.with_config(&loaded.config)))
+        }
+        Commands::Diff(args) => commands::diff::run(args),
+        Commands::DiffResolve(args) => commands::diff_resolve::run(args),
+        Commands::Eval(args) => {
+            load().and_then(|loaded| commands::eval::run(args.with_config(&loaded.config)))
+        }
+        Commands::Serve(args) => {
+            load().and_then(|loaded| commands::serve::run(args.with_config(&loaded.config)))
+        }
+        Commands::Mcp(args) => {
+            load().and_then(|loaded| commands::mcp::run(args.with_config(&loaded.config)))
+        }
+        Commands::Config(command) => {
+            load().and_then(|loaded| commands::config::run(command, &loaded))
+        }
     };
 
     if let Err(e) = result {
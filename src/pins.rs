@@ -0,0 +1,103 @@
+use serde::Serialize;
+
+use context_core::cache::ContextCache;
+
+use crate::cache;
+use crate::exit_codes::{CliError, INVALID_BUDGET};
+
+/// Documents included in every resolve ahead of normal selection.
+#[derive(Default)]
+pub struct Pins {
+    /// Output entries for the pinned documents, in pin order.
+    pub entries: Vec<PinnedDocument>,
+    pub tokens: usize,
+}
+
+/// A pinned document as it appears in a result's `documents`.
+#[derive(Serialize, Clone)]
+pub struct PinnedDocument {
+    pub id: String,
+    pub version: String,
+    pub source: String,
+    pub content: String,
+    pub tokens: usize,
+    pub pinned: bool,
+}
+
+/// `selection.pinned` of a resolve result.
+#[derive(Serialize)]
+pub struct PinSummary {
+    pub ids: Vec<String>,
+    pub tokens: usize,
+    pub selection_budget: usize,
+}
+
+impl Pins {
+    /// Take the pinned documents out of the cache's candidates.
+    ///
+    /// Each ID must name a document in the cache. Pinned documents are
+    /// removed from the manifest so selection cannot choose them twice.
+    pub fn take(cache: &mut ContextCache, ids: &[String]) -> Result<Self, CliError> {
+        let mut pins = Pins::default();
+        for id in ids {
+            if pins.entries.iter().any(|entry| entry.id == *id) {
+                continue;
+            }
+            let entry = cache
+                .manifest
+                .documents
+                .iter()
+                .find(|entry| entry.id.to_string() == *id)
+                .ok_or_else(|| CliError::document_not_found(format!("pinned {id}")))?;
+            let stored = cache::read_document(&cache.root, &entry.file)?;
+            let content = stored["content"].as_str().unwrap_or_default();
+            let tokens = cache::document_tokens(&cache.root, &entry.file)?;
+
+            pins.tokens += tokens;
+            pins.entries.push(PinnedDocument {
+                id: id.clone(),
+                version: stored["version"].as_str().unwrap_or_default().to_string(),
+                source: stored["source"].as_str().unwrap_or_default().to_string(),
+                content: content.to_string(),
+                tokens,
+                pinned: true,
+            });
+        }
+
+        cache
+            .manifest
+            .documents
+            .retain(|entry| !ids.contains(&entry.id.to_string()));
+        Ok(pins)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Budget left for normal selection after the pins are reserved.
+    pub fn remaining(&self, budget: usize) -> Result<usize, CliError> {
+        budget.checked_sub(self.tokens).ok_or_else(|| {
+            CliError::new(
+                INVALID_BUDGET,
+                format!(
+                    "pinned documents need {} tokens, more than the budget of {budget}",
+                    self.tokens
+                ),
+            )
+        })
+    }
+
+    /// The pins to record in a result's `selection` block, with the budget
+    /// left for selection, or `None` when nothing is pinned.
+    pub fn summary(&self, selection_budget: usize) -> Option<PinSummary> {
+        if self.is_empty() {
+            return None;
+        }
+        Some(PinSummary {
+            ids: self.entries.iter().map(|entry| entry.id.clone()).collect(),
+            tokens: self.tokens,
+            selection_budget,
+        })
+    }
+}
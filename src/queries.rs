@@ -0,0 +1,183 @@
+use std::io::Read;
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::Value;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::exit_codes::{CliError, INVALID_BUDGET, INVALID_QUERY};
+
+/// Maximum query length, in characters after normalization.
+pub const MAX_QUERY_CHARS: usize = 4096;
+
+/// A validated query and the normalization rules that changed it.
+pub struct NormalizedQuery {
+    pub text: String,
+    pub original: String,
+    /// Rules that changed the query, in the order applied.
+    pub rules: Vec<&'static str>,
+}
+
+impl NormalizedQuery {
+    /// Validate and normalize a raw query.
+    ///
+    /// NUL and other control characters are rejected, except tab, line feed,
+    /// and carriage return, which are whitespace. The query is then converted
+    /// to Unicode NFC, runs of whitespace are collapsed to one space, leading
+    /// and trailing whitespace is trimmed, and the result must be at most
+    /// [`MAX_QUERY_CHARS`] characters.
+    pub fn new(raw: &str) -> Result<Self, String> {
+        if let Some((index, c)) = raw
+            .char_indices()
+            .find(|&(_, c)| c.is_control() && !matches!(c, '\t' | '\n' | '\r'))
+        {
+            return Err(format!(
+                "query contains control character U+{:04X} at byte {index}",
+                c as u32
+            ));
+        }
+
+        let mut rules = Vec::new();
+        let nfc: String = raw.nfc().collect();
+        if nfc != raw {
+            rules.push("unicode_nfc");
+        }
+        let collapsed = nfc.split_whitespace().collect::<Vec<_>>().join(" ");
+        if collapsed != nfc {
+            rules.push("whitespace_collapse");
+        }
+
+        let length = collapsed.chars().count();
+        if length > MAX_QUERY_CHARS {
+            return Err(format!("query is {length} characters; the maximum is {MAX_QUERY_CHARS}"));
+        }
+
+        Ok(NormalizedQuery {
+            text: collapsed,
+            original: raw.to_string(),
+            rules,
+        })
+    }
+
+    /// Validate a query given on the command line or over a protocol.
+    pub fn parse(raw: &str) -> Result<Self, CliError> {
+        Self::new(raw).map_err(|e| CliError::new(INVALID_QUERY, e))
+    }
+
+    /// The normalization to record in a result's `selection` block, or
+    /// `None` when the query needed none.
+    pub fn normalization(&self) -> Option<QueryNormalization> {
+        if self.rules.is_empty() {
+            return None;
+        }
+        Some(QueryNormalization {
+            original: self.original.clone(),
+            normalized: self.text.clone(),
+            rules: self.rules.clone(),
+        })
+    }
+}
+
+/// `selection.query_normalization` of a resolve result.
+#[derive(Serialize)]
+pub struct QueryNormalization {
+    pub original: String,
+    pub normalized: String,
+    pub rules: Vec<&'static str>,
+}
+
+/// One entry of a query file.
+pub struct BatchQuery {
+    /// Caller-supplied identifier, echoed back verbatim.
+    pub id: Option<Value>,
+    /// The normalized query.
+    pub query: String,
+    /// Normalization applied to the query as written.
+    pub normalized: NormalizedQuery,
+    pub budget: usize,
+    /// The full parsed line, for commands that read additional fields.
+    pub fields: Value,
+}
+
+/// Read a JSON-lines query file.
+///
+/// Each non-blank line is an object with a `query` string, a `budget`
+/// (optional when `default_budget` is given), and an optional `id`. Queries
+/// are validated and normalized as by [`NormalizedQuery::new`].
+pub fn read_query_file(
+    path: &Path,
+    default_budget: Option<usize>,
+) -> Result<Vec<BatchQuery>, CliError> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| CliError::io_error(format!("reading {}: {e}", path.display())))?;
+
+    let mut queries = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let at = format!("{}:{}", path.display(), index + 1);
+
+        let value: Value = serde_json::from_str(line)
+            .map_err(|e| CliError::new(INVALID_QUERY, format!("{at}: invalid JSON: {e}")))?;
+        let query = value["query"].as_str().ok_or_else(|| {
+            CliError::new(INVALID_QUERY, format!("{at}: missing \"query\" string"))
+        })?;
+        let normalized = NormalizedQuery::new(query)
+            .map_err(|e| CliError::new(INVALID_QUERY, format!("{at}: {e}")))?;
+        let budget = match value.get("budget") {
+            Some(budget) => budget
+                .as_u64()
+                .and_then(|b| usize::try_from(b).ok())
+                .ok_or_else(|| {
+                    CliError::new(
+                        INVALID_BUDGET,
+                        format!("{at}: \"budget\" must be a non-negative integer"),
+                    )
+                })?,
+            None => default_budget.ok_or_else(|| {
+                CliError::new(INVALID_BUDGET, format!("{at}: missing \"budget\""))
+            })?,
+        };
+
+        queries.push(BatchQuery {
+            id: value.get("id").cloned(),
+            query: normalized.text.clone(),
+            normalized,
+            budget,
+            fields: value,
+        });
+    }
+    Ok(queries)
+}
+
+/// Read a query from `path`, or from stdin when `path` is `-`.
+///
+/// The input must be valid UTF-8. One trailing line ending is removed so
+/// that files written by editors resolve the same as `--query`.
+pub fn read_query_text(path: &Path) -> Result<String, CliError> {
+    let bytes = if path == Path::new("-") {
+        let mut bytes = Vec::new();
+        std::io::stdin()
+            .read_to_end(&mut bytes)
+            .map_err(|e| CliError::io_error(format!("reading stdin: {e}")))?;
+        bytes
+    } else {
+        std::fs::read(path)
+            .map_err(|e| CliError::io_error(format!("reading {}: {e}", path.display())))?
+    };
+
+    let mut text = String::from_utf8(bytes).map_err(|e| {
+        CliError::new(
+            INVALID_QUERY,
+            format!("query is not valid UTF-8 (byte {})", e.utf8_error().valid_up_to()),
+        )
+    })?;
+    if text.ends_with('\n') {
+        text.pop();
+        if text.ends_with('\r') {
+            text.pop();
+        }
+    }
+    Ok(text)
+}
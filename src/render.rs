@@ -0,0 +1,321 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use context_core::cache::ContextCache;
+
+use crate::cache;
+use crate::exit_codes::{CliError, USAGE_ERROR};
+
+/// Per-document header fields shown by the prompt-ready formats.
+#[derive(ValueEnum, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum HeaderField {
+    Rank,
+    Id,
+    Source,
+    Score,
+    Tokens,
+}
+
+impl HeaderField {
+    pub const DEFAULT: &'static [HeaderField] =
+        &[HeaderField::Id, HeaderField::Source, HeaderField::Score];
+
+    fn name(self) -> &'static str {
+        match self {
+            HeaderField::Rank => "rank",
+            HeaderField::Id => "id",
+            HeaderField::Source => "source",
+            HeaderField::Score => "score",
+            HeaderField::Tokens => "tokens",
+        }
+    }
+}
+
+/// A selected document with the stored fields needed for rendering.
+pub struct Selected {
+    pub rank: usize,
+    pub id: String,
+    pub source: String,
+    pub score: Option<String>,
+    pub tokens: Option<u64>,
+    pub content: String,
+}
+
+impl Selected {
+    fn field(&self, field: HeaderField) -> Option<String> {
+        match field {
+            HeaderField::Rank => Some(self.rank.to_string()),
+            HeaderField::Id => Some(self.id.clone()),
+            HeaderField::Source => Some(self.source.clone()),
+            HeaderField::Score => self.score.clone(),
+            HeaderField::Tokens => self.tokens.map(|t| t.to_string()),
+        }
+    }
+}
+
+/// The documents of a serialized resolve result, in rank order, joined
+/// with their stored source and content.
+pub fn selected(cache: &ContextCache, result: &Value) -> Result<Vec<Selected>, CliError> {
+    let docs = result["documents"].as_array().cloned().unwrap_or_default();
+    let mut selected = Vec::with_capacity(docs.len());
+    for (index, doc) in docs.iter().enumerate() {
+        let id = doc["id"].as_str().unwrap_or_default().to_string();
+        // Pinned entries carry their stored fields, and are no longer
+        // candidates in the manifest
+        let stored = if doc["pinned"] == true {
+            doc.clone()
+        } else {
+            let entry = cache
+                .manifest
+                .documents
+                .iter()
+                .find(|entry| entry.id.to_string() == id)
+                .ok_or_else(|| CliError::cache_invalid(format!("{id} is not in the manifest")))?;
+            cache::read_document(&cache.root, &entry.file)?
+        };
+        selected.push(Selected {
+            rank: index + 1,
+            source: stored["source"].as_str().unwrap_or_default().replace('\\', "/"),
+            score: doc.get("score").filter(|s| !s.is_null()).map(|s| s.to_string()),
+            tokens: doc["tokens"].as_u64(),
+            content: stored["content"].as_str().unwrap_or_default().to_string(),
+            id,
+        });
+    }
+    Ok(selected)
+}
+
+/// Interpret `\n`, `\t`, and `\\` escapes in a separator given on the
+/// command line.
+pub fn unescape(separator: &str) -> String {
+    let mut out = String::with_capacity(separator.len());
+    let mut chars = separator.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('\\') => out.push('\\'),
+            Some(other) => {
+                out.push('\\');
+                out.push(other);
+            }
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Render as Markdown: one `##` section per document, header fields as a
+/// list, then the content.
+pub fn markdown(docs: &[Selected], header: &[HeaderField], separator: &str) -> String {
+    let blocks: Vec<String> = docs
+        .iter()
+        .map(|doc| {
+            let mut block = format!("## Document {}\n\n", doc.rank);
+            for &field in header {
+                if let Some(value) = doc.field(field) {
+                    block.push_str(&format!("- **{}**: {value}\n", field.name()));
+                }
+            }
+            if !header.is_empty() {
+                block.push('\n');
+            }
+            block.push_str(doc.content.trim_end());
+            block.push('\n');
+            block
+        })
+        .collect();
+    blocks.join(separator)
+}
+
+/// Render as plain text: `name: value` header lines, a blank line, then the
+/// content.
+pub fn text(docs: &[Selected], header: &[HeaderField], separator: &str) -> String {
+    let blocks: Vec<String> = docs
+        .iter()
+        .map(|doc| {
+            let mut block = String::new();
+            for &field in header {
+                if let Some(value) = doc.field(field) {
+                    block.push_str(&format!("{}: {value}\n", field.name()));
+                }
+            }
+            if !block.is_empty() {
+                block.push('\n');
+            }
+            block.push_str(doc.content.trim_end());
+            block.push('\n');
+            block
+        })
+        .collect();
+    blocks.join(separator)
+}
+
+/// Render as XML: a `<context>` element holding one `<document>` per
+/// selection, with header fields as attributes.
+pub fn xml(
+    docs: &[Selected],
+    header: &[HeaderField],
+    separator: &str,
+    query: &str,
+    budget: usize,
+) -> String {
+    let blocks: Vec<String> = docs
+        .iter()
+        .map(|doc| {
+            let mut block = String::from("<document");
+            for &field in header {
+                if let Some(value) = doc.field(field) {
+                    block.push_str(&format!(" {}=\"{}\"", field.name(), escape_xml(&value)));
+                }
+            }
+            block.push_str(">\n");
+            block.push_str(&escape_xml(doc.content.trim_end()));
+            block.push_str("\n</document>\n");
+            block
+        })
+        .collect();
+    format!(
+        "<context query=\"{}\" budget=\"{budget}\">\n{}</context>\n",
+        escape_xml(query),
+        blocks.join(separator)
+    )
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Variables available everywhere in a template.
+const GLOBAL_VARIABLES: &[&str] = &["query", "budget", "document_count", "tokens_used"];
+
+/// Variables available inside `{{#documents}}`.
+const DOCUMENT_VARIABLES: &[&str] = &["rank", "id", "source", "score", "tokens", "content"];
+
+/// A parsed output template.
+///
+/// The language has two constructs: `{{name}}` substitutes a variable, and
+/// `{{#documents}}...{{/documents}}` repeats its body once per selected
+/// document in rank order. Anything else is copied verbatim. Unknown
+/// variables and unbalanced sections are rejected when parsing.
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+enum Node {
+    Text(String),
+    Variable(String),
+    Documents(Vec<Node>),
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self, CliError> {
+        let invalid = |message: String| CliError::new(USAGE_ERROR, format!("template: {message}"));
+
+        // Each open section collects its nodes until the matching close tag
+        let mut stack: Vec<Vec<Node>> = vec![Vec::new()];
+        let mut rest = source;
+        while let Some(start) = rest.find("{{") {
+            if start > 0 {
+                stack.last_mut().unwrap().push(Node::Text(rest[..start].to_string()));
+            }
+            let end = rest[start..]
+                .find("}}")
+                .ok_or_else(|| invalid("unclosed {{".to_string()))?;
+            let tag = rest[start + 2..start + end].trim();
+            rest = &rest[start + end + 2..];
+
+            match tag {
+                "#documents" => {
+                    if stack.len() > 1 {
+                        return Err(invalid("{{#documents}} cannot be nested".to_string()));
+                    }
+                    stack.push(Vec::new());
+                }
+                "/documents" => {
+                    if stack.len() < 2 {
+                        return Err(invalid("{{/documents}} without {{#documents}}".to_string()));
+                    }
+                    let body = stack.pop().unwrap();
+                    stack.last_mut().unwrap().push(Node::Documents(body));
+                }
+                name => {
+                    let in_documents = stack.len() > 1;
+                    if !GLOBAL_VARIABLES.contains(&name)
+                        && !(in_documents && DOCUMENT_VARIABLES.contains(&name))
+                    {
+                        return Err(invalid(format!("unknown variable {{{{{name}}}}}")));
+                    }
+                    stack.last_mut().unwrap().push(Node::Variable(name.to_string()));
+                }
+            }
+        }
+        if stack.len() > 1 {
+            return Err(invalid("{{#documents}} is never closed".to_string()));
+        }
+        if !rest.is_empty() {
+            stack.last_mut().unwrap().push(Node::Text(rest.to_string()));
+        }
+
+        Ok(Template {
+            nodes: stack.pop().unwrap(),
+        })
+    }
+
+    pub fn render(&self, docs: &[Selected], query: &str, budget: usize) -> String {
+        let tokens_used: u64 = docs.iter().filter_map(|doc| doc.tokens).sum();
+        let global = |name: &str| match name {
+            "query" => query.to_string(),
+            "budget" => budget.to_string(),
+            "document_count" => docs.len().to_string(),
+            "tokens_used" => tokens_used.to_string(),
+            _ => String::new(),
+        };
+        let document = |doc: &Selected, name: &str| match name {
+            "rank" => doc.rank.to_string(),
+            "id" => doc.id.clone(),
+            "source" => doc.source.clone(),
+            "score" => doc.score.clone().unwrap_or_default(),
+            "tokens" => doc.tokens.map(|t| t.to_string()).unwrap_or_default(),
+            "content" => doc.content.clone(),
+            _ => global(name),
+        };
+
+        let mut out = String::new();
+        for node in &self.nodes {
+            match node {
+                Node::Text(text) => out.push_str(text),
+                Node::Variable(name) => out.push_str(&global(name)),
+                Node::Documents(body) => {
+                    for doc in docs {
+                        for node in body {
+                            match node {
+                                Node::Text(text) => out.push_str(text),
+                                Node::Variable(name) => out.push_str(&document(doc, name)),
+                                Node::Documents(_) => {}
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        out
+    }
+}
@@ -0,0 +1,223 @@
+use std::path::{Path, PathBuf};
+
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use walkdir::WalkDir;
+
+use crate::exit_codes::{CliError, USAGE_ERROR};
+
+/// Gitignore-style file honored in every directory under `--sources`.
+pub const IGNORE_FILENAME: &str = ".contextignore";
+
+/// Include/exclude glob rules matched against `/`-separated paths relative
+/// to the sources root.
+pub struct SourceRules {
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+    /// Directories whose whole contents `exclude` matches: the prefixes of
+    /// patterns ending in `/**`.
+    exclude_dirs: GlobSet,
+}
+
+impl SourceRules {
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self, CliError> {
+        let include = if include.is_empty() {
+            None
+        } else {
+            Some(glob_set(include)?)
+        };
+        let dir_patterns: Vec<String> = exclude
+            .iter()
+            .filter_map(|pattern| pattern.strip_suffix("/**"))
+            .filter(|prefix| !prefix.is_empty())
+            .map(str::to_string)
+            .collect();
+        Ok(Self {
+            include,
+            exclude: glob_set(exclude)?,
+            exclude_dirs: glob_set(&dir_patterns)?,
+        })
+    }
+
+    fn excludes_dir(&self, rel: &str) -> bool {
+        self.exclude_dirs.is_match(rel)
+    }
+
+    fn exclusion(&self, rel: &str) -> Option<ExcludeReason> {
+        if self.exclude.is_match(rel) {
+            return Some(ExcludeReason::ExcludePattern);
+        }
+        match &self.include {
+            Some(include) if !include.is_match(rel) => Some(ExcludeReason::NotIncluded),
+            _ => None,
+        }
+    }
+}
+
+fn glob_set(patterns: &[String]) -> Result<GlobSet, CliError> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob: Glob = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| CliError::new(USAGE_ERROR, e.to_string()))?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|e| CliError::new(USAGE_ERROR, e.to_string()))
+}
+
+/// Why a path under the sources root was left out of the build.
+#[derive(Clone, Copy)]
+pub enum ExcludeReason {
+    ExcludePattern,
+    NotIncluded,
+    ContextIgnore,
+}
+
+impl ExcludeReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ExcludePattern => "exclude_pattern",
+            Self::NotIncluded => "not_included",
+            Self::ContextIgnore => "contextignore",
+        }
+    }
+}
+
+/// A source file selected for ingestion.
+pub struct SourceFile {
+    pub path: PathBuf,
+    /// Path relative to the sources root, as recorded in the document.
+    pub source: String,
+}
+
+/// A path excluded by the source rules. Excluded directories end in `/`.
+pub struct Excluded {
+    pub source: String,
+    pub reason: ExcludeReason,
+}
+
+/// Result of walking the sources root.
+pub struct Discovery {
+    pub files: Vec<SourceFile>,
+    pub excluded: Vec<Excluded>,
+}
+
+/// Walk `root` in file-name order, applying `.contextignore` files and
+/// `rules` before any document is created. A directory excluded as a whole,
+/// by `.contextignore` or an `--exclude` ending in `/**`, is recorded once
+/// and not descended into.
+pub fn discover(root: &Path, rules: &SourceRules) -> Result<Discovery, CliError> {
+    let mut files = Vec::new();
+    let mut excluded = Vec::new();
+    // Ignore matchers of the directories enclosing the current entry,
+    // outermost first, tagged with the depth of their directory.
+    let mut matchers: Vec<(usize, Gitignore)> = Vec::new();
+
+    let mut walker = WalkDir::new(root).sort_by_file_name().into_iter();
+    while let Some(entry) = walker.next() {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        let path = entry.path();
+        let depth = entry.depth();
+        let is_dir = entry.file_type().is_dir();
+
+        while matchers.last().is_some_and(|(d, _)| *d >= depth) {
+            matchers.pop();
+        }
+
+        if depth > 0 {
+            let rel = relative_path(root, path);
+            if is_ignored(&matchers, path, is_dir) {
+                excluded.push(Excluded {
+                    source: if is_dir { format!("{rel}/") } else { rel },
+                    reason: ExcludeReason::ContextIgnore,
+                });
+                if is_dir {
+                    walker.skip_current_dir();
+                }
+                continue;
+            }
+
+            if is_dir && rules.excludes_dir(&rel) {
+                excluded.push(Excluded {
+                    source: format!("{rel}/"),
+                    reason: ExcludeReason::ExcludePattern,
+                });
+                walker.skip_current_dir();
+                continue;
+            }
+
+            if !is_dir && path.is_file() {
+                // Ignore files configure discovery; they are never sources
+                if entry.file_name() == IGNORE_FILENAME {
+                    continue;
+                }
+                if let Some(reason) = rules.exclusion(&rel) {
+                    excluded.push(Excluded {
+                        source: rel,
+                        reason,
+                    });
+                    continue;
+                }
+                files.push(SourceFile {
+                    path: path.to_path_buf(),
+                    source: path
+                        .strip_prefix(root)
+                        .unwrap_or(path)
+                        .to_string_lossy()
+                        .to_string(),
+                });
+                continue;
+            }
+        }
+
+        if is_dir {
+            if let Some(matcher) = load_ignore_file(path)? {
+                matchers.push((depth, matcher));
+            }
+        }
+    }
+
+    Ok(Discovery { files, excluded })
+}
+
+fn is_ignored(matchers: &[(usize, Gitignore)], path: &Path, is_dir: bool) -> bool {
+    for (_, matcher) in matchers.iter().rev() {
+        match matcher.matched(path, is_dir) {
+            Match::Ignore(_) => return true,
+            Match::Whitelist(_) => return false,
+            Match::None => {}
+        }
+    }
+    false
+}
+
+fn load_ignore_file(dir: &Path) -> Result<Option<Gitignore>, CliError> {
+    let file = dir.join(IGNORE_FILENAME);
+    if !file.is_file() {
+        return Ok(None);
+    }
+    let mut builder = GitignoreBuilder::new(dir);
+    if let Some(err) = builder.add(&file) {
+        return Err(CliError::io_error(format!("{}: {err}", file.display())));
+    }
+    builder
+        .build()
+        .map(Some)
+        .map_err(|e| CliError::io_error(format!("{}: {e}", file.display())))
+}
+
+/// `/`-separated path of `path` relative to `root`.
+pub fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
@@ -0,0 +1,132 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::Value;
+use walkdir::WalkDir;
+
+use context_core::cache::{CacheBuildConfig, CacheBuilder, CacheManifest};
+use context_core::document::{Document, DocumentId, Metadata};
+
+use crate::sources;
+
+/// Outcome of a deep verification of a cache directory.
+#[derive(Serialize)]
+pub struct Verification {
+    pub documents_checked: usize,
+    pub failures: Vec<DocumentFailure>,
+    /// Files in the cache directory not referenced by the manifest.
+    pub orphaned_files: Vec<String>,
+    /// Whether rebuilding from the stored documents reproduces the
+    /// manifest's cache version. `None` when a document failed to load.
+    pub cache_version_matches: Option<bool>,
+}
+
+impl Verification {
+    pub fn is_valid(&self) -> bool {
+        self.failures.is_empty()
+            && self.orphaned_files.is_empty()
+            && self.cache_version_matches == Some(true)
+    }
+}
+
+#[derive(Serialize)]
+pub struct DocumentFailure {
+    pub id: String,
+    pub file: String,
+    pub reason: String,
+}
+
+/// Re-hash every document, check for orphaned files, and recompute the
+/// cache version.
+///
+/// Hashes are recomputed by re-ingesting each stored document through
+/// `Document::ingest`, and the cache version by rebuilding the documents
+/// into a scratch directory, so the checks use exactly the rules the cache
+/// was built with.
+pub fn verify(cache_path: &Path, manifest: &CacheManifest) -> Verification {
+    let mut failures = Vec::new();
+    let mut documents = Vec::new();
+    let mut referenced = BTreeSet::new();
+
+    for entry in &manifest.documents {
+        referenced.insert(entry.file.clone());
+        let id = entry.id.to_string();
+        match rehash(&cache_path.join(&entry.file), &id, &entry.version.to_string()) {
+            Ok(doc) => documents.push(doc),
+            Err(reason) => failures.push(DocumentFailure {
+                id,
+                file: entry.file.clone(),
+                reason,
+            }),
+        }
+    }
+
+    let orphaned_files = WalkDir::new(cache_path)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| sources::relative_path(cache_path, e.path()))
+        .filter(|rel| rel != "manifest.json" && !referenced.contains(rel))
+        .collect();
+
+    let cache_version_matches = if failures.is_empty() {
+        Some(rebuilt_version(documents) == Some(manifest.cache_version.to_string()))
+    } else {
+        None
+    };
+
+    Verification {
+        documents_checked: manifest.documents.len(),
+        failures,
+        orphaned_files,
+        cache_version_matches,
+    }
+}
+
+/// Load a stored document and re-ingest its content, returning the
+/// re-ingested document if its hash matches `expected_version`.
+fn rehash(path: &Path, expected_id: &str, expected_version: &str) -> Result<Document, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("unreadable file: {e}"))?;
+    let stored: Value =
+        serde_json::from_slice(&bytes).map_err(|e| format!("invalid document JSON: {e}"))?;
+
+    let id: DocumentId = serde_json::from_value(stored["id"].clone())
+        .map_err(|e| format!("invalid document id: {e}"))?;
+    if id.to_string() != expected_id {
+        return Err(format!("document id is {id}"));
+    }
+    if stored["version"].as_str() != Some(expected_version) {
+        return Err("stored version does not match manifest".to_string());
+    }
+
+    let source = stored["source"].as_str().unwrap_or_default().to_string();
+    let content = stored["content"]
+        .as_str()
+        .ok_or("missing content")?
+        .as_bytes()
+        .to_vec();
+    let metadata: Metadata = serde_json::from_value(stored["metadata"].clone())
+        .map_err(|e| format!("invalid metadata: {e}"))?;
+
+    let doc = Document::ingest(id, source, content, metadata)
+        .map_err(|e| format!("re-ingestion failed: {e}"))?;
+    let rehashed = serde_json::to_value(&doc).map_err(|e| e.to_string())?;
+    if rehashed["version"].as_str() != Some(expected_version) {
+        return Err("content hash mismatch".to_string());
+    }
+    Ok(doc)
+}
+
+/// Cache version produced by rebuilding `documents` from scratch.
+fn rebuilt_version(documents: Vec<Document>) -> Option<String> {
+    let scratch = std::env::temp_dir().join(format!("context-verify-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&scratch);
+    let version = CacheBuilder::new(CacheBuildConfig::v0())
+        .build(documents, &scratch)
+        .ok()
+        .map(|cache| cache.manifest.cache_version.to_string());
+    let _ = std::fs::remove_dir_all(&scratch);
+    version
+}
@@ -0,0 +1,836 @@
+//! Build command tests.
+//!
+//! Validates how `context build` turns source files into cache
+//! documents: front matter, source selection, and build options.
+
+use std::fs;
+
+mod common;
+
+use common::context_bin;
+
+const IO_ERROR: i32 = 6;
+const INTERNAL_ERROR: i32 = 7;
+
+/// Load every stored document in `cache` as JSON, in manifest order.
+fn stored_documents(cache: &std::path::Path) -> Vec<serde_json::Value> {
+    let manifest: serde_json::Value =
+        serde_json::from_slice(&fs::read(cache.join("manifest.json")).unwrap()).unwrap();
+    manifest["documents"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| {
+            let file = entry["file"].as_str().unwrap();
+            serde_json::from_slice(&fs::read(cache.join(file)).unwrap()).unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn yaml_front_matter_becomes_metadata() {
+    let tmp = tempfile::tempdir().unwrap();
+    let sources = tmp.path().join("sources");
+    fs::create_dir_all(&sources).unwrap();
+    fs::write(
+        sources.join("runbook.md"),
+        "---\ntitle: Runbook\ntags: [ops, oncall]\n---\nRestart the service.\n",
+    )
+    .unwrap();
+
+    let cache = tmp.path().join("cache");
+    let output = context_bin()
+        .args(["build", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(&cache)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "build failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let docs = stored_documents(&cache);
+    assert_eq!(docs.len(), 1);
+    assert_eq!(docs[0]["metadata"]["title"].as_str().unwrap(), "Runbook");
+    assert!(
+        !docs[0]["content"].as_str().unwrap().contains("title:"),
+        "Front matter must be stripped from content"
+    );
+}
+
+#[test]
+fn toml_front_matter_becomes_metadata() {
+    let tmp = tempfile::tempdir().unwrap();
+    let sources = tmp.path().join("sources");
+    fs::create_dir_all(&sources).unwrap();
+    fs::write(
+        sources.join("guide.md"),
+        "+++\ntitle = \"Guide\"\nowner = \"platform\"\n+++\nGuide body.\n",
+    )
+    .unwrap();
+
+    let cache = tmp.path().join("cache");
+    let output = context_bin()
+        .args(["build", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(&cache)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let docs = stored_documents(&cache);
+    assert_eq!(docs[0]["metadata"]["owner"].as_str().unwrap(), "platform");
+}
+
+#[test]
+fn front_matter_keys_are_stored_sorted() {
+    let tmp = tempfile::tempdir().unwrap();
+    let sources = tmp.path().join("sources");
+    fs::create_dir_all(&sources).unwrap();
+    fs::write(
+        sources.join("guide.md"),
+        "---\nversion: 2\ntitle: Guide\nowner: docs\n\
+         review: {zulu_step: 1, alpha_step: 2}\naudience: ops\n---\nBody.\n",
+    )
+    .unwrap();
+
+    let cache = tmp.path().join("cache");
+    let output = context_bin()
+        .args(["build", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(&cache)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    // Check the stored bytes, since parsing them would sort keys anyway
+    let manifest: serde_json::Value =
+        serde_json::from_slice(&fs::read(cache.join("manifest.json")).unwrap()).unwrap();
+    let file = manifest["documents"][0]["file"].as_str().unwrap();
+    let stored = fs::read_to_string(cache.join(file)).unwrap();
+    let position = |key: &str| {
+        stored
+            .find(&format!("\"{key}\""))
+            .unwrap_or_else(|| panic!("{key} missing from {stored}"))
+    };
+    let keys = ["audience", "owner", "review", "title"];
+    for pair in keys.windows(2) {
+        assert!(
+            position(pair[0]) < position(pair[1]),
+            "metadata keys must be sorted: {stored}"
+        );
+    }
+    assert!(
+        position("alpha_step") < position("zulu_step"),
+        "nested metadata keys must be sorted: {stored}"
+    );
+}
+
+#[test]
+fn unknown_front_matter_key_can_be_rejected() {
+    let tmp = tempfile::tempdir().unwrap();
+    let sources = tmp.path().join("sources");
+    fs::create_dir_all(&sources).unwrap();
+    fs::write(
+        sources.join("draft.md"),
+        "---\ntitle: Draft\nreviewer: someone\n---\nBody.\n",
+    )
+    .unwrap();
+
+    let output = context_bin()
+        .args(["build", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(tmp.path().join("cache"))
+        .args(["--unknown-metadata", "reject"])
+        .output()
+        .unwrap();
+
+    assert_eq!(
+        output.status.code().unwrap(),
+        INTERNAL_ERROR,
+        "Rejected front-matter key should return exit code {}. stderr: {}",
+        INTERNAL_ERROR,
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// Sorted `source` values of every stored document in `cache`.
+fn stored_sources(cache: &std::path::Path) -> Vec<String> {
+    let mut sources: Vec<String> = stored_documents(cache)
+        .iter()
+        .map(|doc| doc["source"].as_str().unwrap().replace('\\', "/"))
+        .collect();
+    sources.sort();
+    sources
+}
+
+fn write_tree(root: &std::path::Path, files: &[(&str, &str)]) {
+    for (rel_path, content) in files {
+        let path = root.join(rel_path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+    }
+}
+
+#[test]
+fn include_and_exclude_globs_filter_sources() {
+    let tmp = tempfile::tempdir().unwrap();
+    let sources = tmp.path().join("sources");
+    write_tree(
+        &sources,
+        &[
+            ("docs/api.md", "API reference"),
+            ("docs/drafts/wip.md", "Work in progress"),
+            ("docs/drafts/old/notes.md", "Older work"),
+            ("node_modules/pkg/README.md", "Vendored readme"),
+            ("node_modules/pkg/index.js", "module.exports = {};"),
+        ],
+    );
+
+    let cache = tmp.path().join("cache");
+    let output = context_bin()
+        .args(["build", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(&cache)
+        .args(["--include", "docs/**", "--exclude", "docs/drafts/**"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "build failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    assert_eq!(stored_sources(&cache), vec!["docs/api.md".to_string()]);
+
+    // An excluded directory is listed once and not walked
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Excluded docs/drafts/ (exclude_pattern)"), "got: {stderr}");
+    assert!(!stderr.contains("wip.md"), "got: {stderr}");
+    assert!(stderr.contains("Excluded node_modules/pkg/README.md (not_included)"));
+    assert!(!stderr.contains("index.js"), "got: {stderr}");
+}
+
+#[test]
+fn contextignore_is_honored_at_every_level() {
+    let tmp = tempfile::tempdir().unwrap();
+    let sources = tmp.path().join("sources");
+    write_tree(
+        &sources,
+        &[
+            (".contextignore", "vendor/\n"),
+            ("vendor/lib.md", "Vendored docs"),
+            ("guide/.contextignore", "*.draft.md\n!keep.draft.md\n"),
+            ("guide/intro.md", "Introduction"),
+            ("guide/next.draft.md", "Unfinished"),
+            ("guide/keep.draft.md", "Kept draft"),
+        ],
+    );
+
+    let cache = tmp.path().join("cache");
+    let output = context_bin()
+        .args(["build", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(&cache)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    assert_eq!(
+        stored_sources(&cache),
+        vec![
+            "guide/intro.md".to_string(),
+            "guide/keep.draft.md".to_string()
+        ]
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Excluded vendor/ (contextignore)"));
+}
+
+#[test]
+fn only_markdown_is_ingested_by_default() {
+    let tmp = tempfile::tempdir().unwrap();
+    let sources = tmp.path().join("sources");
+    write_tree(
+        &sources,
+        &[("guide.md", "Markdown guide"), ("notes.txt", "Plain notes")],
+    );
+
+    let cache = tmp.path().join("cache");
+    let output = context_bin()
+        .args(["build", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(&cache)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    assert_eq!(stored_sources(&cache), vec!["guide.md".to_string()]);
+}
+
+#[test]
+fn html_sources_are_normalized_to_text() {
+    let tmp = tempfile::tempdir().unwrap();
+    let sources = tmp.path().join("sources");
+    write_tree(
+        &sources,
+        &[(
+            "page.html",
+            "<html><head><style>p { color: red; }</style></head>\
+             <body><h1>Release &amp; Deploy</h1><p>Ship it.</p></body></html>",
+        )],
+    );
+
+    let cache = tmp.path().join("cache");
+    let output = context_bin()
+        .args(["build", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(&cache)
+        .args(["--formats", "md,html"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "build failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let docs = stored_documents(&cache);
+    assert_eq!(docs.len(), 1);
+    let content = docs[0]["content"].as_str().unwrap();
+    assert!(content.contains("# Release & Deploy"), "got: {content}");
+    assert!(content.contains("Ship it."));
+    assert!(!content.contains('<'));
+    assert!(!content.contains("color"));
+}
+
+#[test]
+fn rst_titles_become_headings() {
+    let tmp = tempfile::tempdir().unwrap();
+    let sources = tmp.path().join("sources");
+    write_tree(
+        &sources,
+        &[(
+            "ops.rst",
+            "Operations\n==========\n\n.. note::\n\n   Read this first.\n\nRollback\n--------\n\nRevert the release.\n",
+        )],
+    );
+
+    let cache = tmp.path().join("cache");
+    let output = context_bin()
+        .args(["build", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(&cache)
+        .args(["--formats", "rst"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let docs = stored_documents(&cache);
+    let content = docs[0]["content"].as_str().unwrap();
+    assert!(content.contains("# Operations"), "got: {content}");
+    assert!(content.contains("## Rollback"), "got: {content}");
+    assert!(!content.contains(".. note::"));
+}
+
+#[test]
+fn chunk_by_heading_splits_sections() {
+    let tmp = tempfile::tempdir().unwrap();
+    let sources = tmp.path().join("sources");
+    write_tree(
+        &sources,
+        &[(
+            "runbook.md",
+            "# Runbook\n\nOverview.\n\n## Deploy\n\nRoll out.\n\n```\n# not a heading\n```\n\n## Rollback\n\nRevert.\n",
+        )],
+    );
+
+    let cache = tmp.path().join("cache");
+    let output = context_bin()
+        .args(["build", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(&cache)
+        .args(["--chunk-by", "heading"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "build failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let docs = stored_documents(&cache);
+    assert_eq!(docs.len(), 3, "Expected one document per heading section");
+
+    let deploy = docs
+        .iter()
+        .find(|doc| doc["content"].as_str().unwrap().starts_with("## Deploy"))
+        .expect("missing Deploy section");
+    assert!(deploy["id"].as_str().unwrap().ends_with("#deploy"));
+    assert!(deploy["content"].as_str().unwrap().contains("# not a heading"));
+    assert_eq!(deploy["metadata"]["parent"].as_str().unwrap(), "runbook.md");
+    assert_eq!(
+        deploy["metadata"]["heading_path"],
+        serde_json::json!(["Runbook", "Deploy"])
+    );
+}
+
+#[test]
+fn max_chunk_tokens_splits_long_sections() {
+    let tmp = tempfile::tempdir().unwrap();
+    let sources = tmp.path().join("sources");
+    let paragraph = "Each rollout step is verified against the staging health checks. ";
+    let details: Vec<String> = (1..=8).map(|i| format!("Step {i}. {paragraph}")).collect();
+    let runbook = format!(
+        "# Runbook\n\n## Details\n\n{}\n\n## Notes\n\nFirst.\n\n## Notes\n\nSecond.\n",
+        details.join("\n\n")
+    );
+    write_tree(&sources, &[("runbook.md", runbook.as_str())]);
+
+    let cache = tmp.path().join("cache");
+    let output = context_bin()
+        .args(["build", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(&cache)
+        .args(["--chunk-by", "heading", "--max-chunk-tokens", "40"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "build failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // Token counts as `resolve` charges them against the budget
+    let ls = context_bin()
+        .args(["ls", "--cache"])
+        .arg(&cache)
+        .output()
+        .unwrap();
+    assert!(ls.status.success());
+    let rows: Vec<serde_json::Value> = String::from_utf8(ls.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let ids: Vec<&str> = rows.iter().map(|row| row["id"].as_str().unwrap()).collect();
+
+    let parts: Vec<&serde_json::Value> = rows
+        .iter()
+        .filter(|row| row["id"].as_str().unwrap().contains("#details"))
+        .collect();
+    assert!(parts.len() > 1, "Expected the Details section to split, got: {ids:?}");
+    for part in &parts {
+        let tokens = part["token_estimate"].as_u64().unwrap();
+        assert!(tokens <= 40, "{} has {tokens} tokens", part["id"]);
+    }
+    assert!(ids.iter().any(|id| id.ends_with("#details")), "got: {ids:?}");
+    assert!(ids.iter().any(|id| id.ends_with("#details--2")), "got: {ids:?}");
+
+    assert!(ids.iter().any(|id| id.ends_with("#notes")), "got: {ids:?}");
+    assert!(ids.iter().any(|id| id.ends_with("#notes-1")), "got: {ids:?}");
+}
+
+#[test]
+fn max_chunk_tokens_requires_chunk_by() {
+    let tmp = tempfile::tempdir().unwrap();
+    let sources = tmp.path().join("sources");
+    write_tree(&sources, &[("a.md", "A")]);
+
+    let output = context_bin()
+        .args(["build", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(tmp.path().join("cache"))
+        .args(["--max-chunk-tokens", "100"])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+}
+
+#[test]
+fn watch_starts_from_an_existing_cache() {
+    use std::io::{BufRead, BufReader};
+    use std::process::Stdio;
+
+    let tmp = tempfile::tempdir().unwrap();
+    let sources = tmp.path().join("sources");
+    write_tree(&sources, &[("alpha.md", "Alpha notes.\n")]);
+    let cache = tmp.path().join("cache");
+    let build = context_bin()
+        .args(["build", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(&cache)
+        .output()
+        .unwrap();
+    assert!(build.status.success());
+
+    // Edited while no watcher was running
+    write_tree(&sources, &[("beta.md", "Beta notes.\n")]);
+    let mut child = context_bin()
+        .args(["build", "--watch", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(&cache)
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let startup: Vec<String> = BufReader::new(child.stderr.take().unwrap())
+        .lines()
+        .map(Result::unwrap)
+        .take_while(|line| !line.starts_with("Watching"))
+        .collect();
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    assert!(
+        startup.iter().any(|line| line.contains("(1 reused)")),
+        "The first build must reuse the existing cache: {startup:?}"
+    );
+    assert_eq!(stored_sources(&cache), vec!["alpha.md".to_string(), "beta.md".to_string()]);
+}
+
+#[test]
+fn watch_rebuilds_and_reports_changed_queries() {
+    use std::io::{BufRead, BufReader};
+    use std::process::Stdio;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    let tmp = tempfile::tempdir().unwrap();
+    let sources = tmp.path().join("sources");
+    write_tree(&sources, &[("alpha.md", "Alpha notes.\n")]);
+    let queries = tmp.path().join("queries.jsonl");
+    fs::write(&queries, "{\"query\": \"gamma\", \"budget\": 1000, \"id\": \"g\"}\n").unwrap();
+
+    let cache = tmp.path().join("cache");
+    let mut child = context_bin()
+        .args(["build", "--watch", "--debounce", "50", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(&cache)
+        .arg("--queries")
+        .arg(&queries)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    // Wait until the initial build is done and the watcher is running
+    let stderr = BufReader::new(child.stderr.take().unwrap());
+    let mut lines = stderr.lines();
+    assert!(lines
+        .by_ref()
+        .map(Result::unwrap)
+        .any(|line| line.starts_with("Watching")));
+    std::thread::spawn(move || lines.for_each(drop));
+
+    let (tx, rx) = mpsc::channel();
+    let stdout = BufReader::new(child.stdout.take().unwrap());
+    std::thread::spawn(move || {
+        for line in stdout.lines() {
+            let _ = tx.send(line.unwrap());
+        }
+    });
+
+    fs::write(sources.join("gamma.md"), "Gamma rollout gamma checklist.\n").unwrap();
+    let line = rx.recv_timeout(Duration::from_secs(30));
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    let report: serde_json::Value = serde_json::from_str(&line.unwrap()).unwrap();
+    assert_eq!(report["changed_queries"], 1);
+    assert_eq!(report["queries"][0]["id"], "g");
+    let entered = report["queries"][0]["entered"].as_array().unwrap();
+    assert!(entered
+        .iter()
+        .any(|doc| doc["id"].as_str().unwrap().contains("gamma")));
+    assert!(
+        stored_sources(&cache).contains(&"gamma.md".to_string()),
+        "The rebuilt cache must include the new source"
+    );
+}
+
+#[test]
+fn failed_force_build_keeps_existing_cache() {
+    let tmp = tempfile::tempdir().unwrap();
+    let sources = tmp.path().join("sources");
+    write_tree(&sources, &[("guide.md", "Guide body.\n")]);
+
+    let cache = tmp.path().join("cache");
+    let build = |extra: &[&str]| {
+        context_bin()
+            .args(["build", "--sources"])
+            .arg(&sources)
+            .arg("--cache")
+            .arg(&cache)
+            .args(extra)
+            .output()
+            .unwrap()
+    };
+    assert!(build(&[]).status.success());
+    let manifest = fs::read(cache.join("manifest.json")).unwrap();
+
+    // A source that fails mid-build must not cost us the existing cache
+    write_tree(&sources, &[("draft.md", "---\nreviewer: someone\n---\nBody.\n")]);
+    let output = build(&["--force", "--unknown-metadata", "reject"]);
+    assert_eq!(output.status.code().unwrap(), INTERNAL_ERROR);
+
+    assert_eq!(
+        fs::read(cache.join("manifest.json")).unwrap(),
+        manifest,
+        "Existing cache must be left untouched by a failed build"
+    );
+    assert_eq!(stored_sources(&cache), vec!["guide.md".to_string()]);
+    let leftovers: Vec<_> = fs::read_dir(tmp.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .filter(|name| name.ends_with(".staging") || name.ends_with(".old"))
+        .collect();
+    assert!(leftovers.is_empty(), "Unexpected leftovers: {leftovers:?}");
+}
+
+#[cfg(unix)]
+#[test]
+fn rebuild_swaps_cache_link_and_keeps_previous_version() {
+    let tmp = tempfile::tempdir().unwrap();
+    let sources = tmp.path().join("sources");
+    write_tree(&sources, &[("guide.md", "Guide body.\n")]);
+
+    let cache = tmp.path().join("cache");
+    let build = || {
+        let output = context_bin()
+            .args(["build", "--force", "--sources"])
+            .arg(&sources)
+            .arg("--cache")
+            .arg(&cache)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        fs::canonicalize(&cache).unwrap()
+    };
+    let first = build();
+    assert!(fs::symlink_metadata(&cache).unwrap().file_type().is_symlink());
+
+    write_tree(&sources, &[("setup.md", "Setup body.\n")]);
+    let second = build();
+    assert_ne!(first, second, "A new version must get its own directory");
+    assert!(
+        first.join("manifest.json").exists(),
+        "The previous version must outlive the swap for readers still on it"
+    );
+
+    write_tree(&sources, &[("faq.md", "FAQ body.\n")]);
+    build();
+    assert!(!first.exists(), "Versions older than the previous one are removed");
+    assert!(second.exists());
+}
+
+#[test]
+fn concurrent_build_of_same_cache_is_rejected() {
+    use fs2::FileExt;
+
+    let tmp = tempfile::tempdir().unwrap();
+    let sources = tmp.path().join("sources");
+    write_tree(&sources, &[("guide.md", "Guide body.\n")]);
+    let cache = tmp.path().join("cache");
+
+    // Hold the lock as a running build would
+    let lock = fs::File::create(tmp.path().join(".cache.lock")).unwrap();
+    lock.lock_exclusive().unwrap();
+
+    let output = context_bin()
+        .args(["build", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(&cache)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code().unwrap(), IO_ERROR);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("another build"));
+    assert!(!cache.exists());
+
+    lock.unlock().unwrap();
+    let output = context_bin()
+        .args(["build", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(&cache)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "Build must proceed once the lock is released");
+}
+
+#[test]
+fn keep_going_skips_failures_and_writes_report() {
+    let tmp = tempfile::tempdir().unwrap();
+    let sources = tmp.path().join("sources");
+    write_tree(
+        &sources,
+        &[
+            ("good.md", "Good body.\n"),
+            ("empty.md", "\n"),
+            ("notes.txt", "Not a selected format.\n"),
+            ("draft.md", "---\nreviewer: someone\n---\nBody.\n"),
+        ],
+    );
+    fs::write(sources.join("binary.md"), [0xff, 0xfe, 0x00, 0x41]).unwrap();
+
+    let cache = tmp.path().join("cache");
+    let output = context_bin()
+        .args(["build", "--keep-going", "--unknown-metadata", "reject", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(&cache)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "Failures without thresholds must not fail the build: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(stored_sources(&cache), vec!["good.md".to_string()]);
+
+    let report: serde_json::Value =
+        serde_json::from_slice(&fs::read(tmp.path().join(".cache.build-report.json")).unwrap())
+            .unwrap();
+    assert_eq!(
+        report["counts"],
+        serde_json::json!({ "included": 1, "skipped": 3, "failed": 1, "warnings": 3 })
+    );
+    assert_eq!(report["included"][0]["source"], "good.md");
+    let skipped: Vec<(&str, &str)> = report["skipped"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| (s["source"].as_str().unwrap(), s["reason"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        skipped,
+        vec![
+            ("binary.md", "non_utf8"),
+            ("empty.md", "empty"),
+            ("notes.txt", "ignored_extension"),
+        ]
+    );
+    assert_eq!(report["failed"][0]["source"], "draft.md");
+    assert_eq!(report["failed"][0]["reason"], "front_matter");
+
+    let inspect = context_bin()
+        .args(["inspect", "--deep", "--cache"])
+        .arg(&cache)
+        .output()
+        .unwrap();
+    assert!(
+        inspect.status.success(),
+        "A keep-going cache must pass deep inspection: {}",
+        String::from_utf8_lossy(&inspect.stdout)
+    );
+}
+
+#[test]
+fn contextignore_files_are_not_reported_as_sources() {
+    let tmp = tempfile::tempdir().unwrap();
+    let sources = tmp.path().join("sources");
+    write_tree(
+        &sources,
+        &[
+            (".contextignore", "drafts/\n"),
+            ("guide/.contextignore", "*.tmp.md\n"),
+            ("guide/setup.md", "Setup.\n"),
+        ],
+    );
+
+    let report = tmp.path().join("report.json");
+    let output = context_bin()
+        .args(["build", "--keep-going", "--max-warnings", "0", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(tmp.path().join("cache"))
+        .arg("--report")
+        .arg(&report)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "Ignore files must not count as warnings: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let report: serde_json::Value = serde_json::from_slice(&fs::read(&report).unwrap()).unwrap();
+    assert_eq!(report["skipped"], serde_json::json!([]));
+}
+
+#[test]
+fn report_is_deterministic_and_thresholds_fail_the_build() {
+    let tmp = tempfile::tempdir().unwrap();
+    let sources = tmp.path().join("sources");
+    write_tree(
+        &sources,
+        &[
+            ("a.md", "Alpha.\n"),
+            ("b.md", "---\nreviewer: someone\n---\nBody.\n"),
+            ("big.md", &"x".repeat(4096)),
+        ],
+    );
+
+    let build = |name: &str, extra: &[&str]| {
+        let report = tmp.path().join(format!("{name}.json"));
+        let output = context_bin()
+            .args(["build", "--keep-going", "--force", "--unknown-metadata", "reject"])
+            .args(["--max-file-bytes", "1024", "--sources"])
+            .arg(&sources)
+            .arg("--cache")
+            .arg(tmp.path().join(name))
+            .arg("--report")
+            .arg(&report)
+            .args(extra)
+            .output()
+            .unwrap();
+        (output, fs::read(&report).unwrap())
+    };
+
+    let (first, first_report) = build("one", &[]);
+    let (second, second_report) = build("two", &[]);
+    assert!(first.status.success() && second.status.success());
+    assert_eq!(first_report, second_report, "Build report must be deterministic");
+    let report: serde_json::Value = serde_json::from_slice(&first_report).unwrap();
+    assert!(report["cache_version"].is_string());
+    assert!(
+        !tmp.path().join(".one.build-report.json").exists(),
+        "--report replaces the report beside the cache"
+    );
+
+    let (over, _) = build("three", &["--max-failed", "0"]);
+    assert_eq!(over.status.code().unwrap(), 1);
+    let (over, _) = build("four", &["--max-failed", "1", "--max-warnings", "0"]);
+    assert_eq!(over.status.code().unwrap(), 1);
+    let (within, _) = build("five", &["--max-failed", "1", "--max-warnings", "1"]);
+    assert!(within.status.success());
+    assert!(!tmp.path().join("three").exists(), "A failed threshold must not write the cache");
+
+    // A failed threshold leaves the existing cache in place
+    let manifest = fs::read(tmp.path().join("one").join("manifest.json")).unwrap();
+    fs::write(sources.join("c.md"), "Charlie.\n").unwrap();
+    let (over, report) = build("one", &["--max-failed", "0"]);
+    assert_eq!(over.status.code().unwrap(), 1);
+    let report: serde_json::Value = serde_json::from_slice(&report).unwrap();
+    assert_eq!(report["counts"]["failed"], 1, "The report is written even when a threshold fails");
+    assert!(report["cache_version"].is_null(), "No cache was written for this report");
+    assert_eq!(fs::read(tmp.path().join("one").join("manifest.json")).unwrap(), manifest);
+    assert!(!tmp.path().join(".one.staging").exists());
+}
@@ -0,0 +1,50 @@
+//! Helpers shared by the integration tests.
+
+// Each test crate compiles its own copy and uses only some of the helpers
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+pub fn context_bin() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_context"))
+}
+
+/// Write `files` (relative path, content) under `dir/{name}-sources` and
+/// build them into the cache `dir/name`.
+pub fn build_cache(dir: &Path, name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let sources = dir.join(format!("{name}-sources"));
+    for (rel_path, content) in files {
+        let path = sources.join(rel_path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+    }
+
+    let cache = dir.join(name);
+    let build = context_bin()
+        .args(["build", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(&cache)
+        .output()
+        .unwrap();
+    assert!(
+        build.status.success(),
+        "build failed: {}",
+        String::from_utf8_lossy(&build.stderr)
+    );
+    cache
+}
+
+/// The two-document cache `dir/cache` used by the server tests.
+pub fn two_doc_cache(dir: &Path) -> PathBuf {
+    build_cache(
+        dir,
+        "cache",
+        &[
+            ("deployment.md", "Deployment guide for production"),
+            ("api.md", "API reference for REST endpoints"),
+        ],
+    )
+}
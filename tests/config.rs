@@ -0,0 +1,301 @@
+//! Project configuration tests.
+//!
+//! Validates `context.toml` discovery, precedence of command-line flags
+//! over file values, and `context config show` output.
+
+use std::fs;
+
+mod common;
+
+use common::context_bin;
+
+const USAGE_ERROR: i32 = 1;
+
+fn write_project(root: &std::path::Path, config: &str) {
+    let docs = root.join("docs");
+    fs::create_dir_all(&docs).unwrap();
+    fs::write(docs.join("deploy.md"), "Deployment guide for production").unwrap();
+    fs::write(docs.join("api.md"), "API reference for endpoints").unwrap();
+    fs::write(root.join("context.toml"), config).unwrap();
+}
+
+#[test]
+fn config_supplies_build_and_resolve_defaults() {
+    let tmp = tempfile::tempdir().unwrap();
+    write_project(
+        tmp.path(),
+        "cache = \"dist/cache\"\n\n[build]\nsources = \"docs\"\n\n[resolve]\nbudget = 4096\n",
+    );
+
+    // Discovered from a subdirectory of the project
+    let build = context_bin()
+        .arg("build")
+        .current_dir(tmp.path().join("docs"))
+        .output()
+        .unwrap();
+    assert!(
+        build.status.success(),
+        "build failed: {}",
+        String::from_utf8_lossy(&build.stderr)
+    );
+    assert!(tmp.path().join("dist/cache/manifest.json").is_file());
+
+    let resolve = context_bin()
+        .args(["resolve", "--query", "deployment"])
+        .current_dir(tmp.path())
+        .output()
+        .unwrap();
+    assert!(
+        resolve.status.success(),
+        "resolve failed: {}",
+        String::from_utf8_lossy(&resolve.stderr)
+    );
+    let output: serde_json::Value = serde_json::from_slice(&resolve.stdout).unwrap();
+    assert_eq!(output["selection"]["budget"].as_u64().unwrap(), 4096);
+}
+
+#[test]
+fn command_line_flags_override_config() {
+    let tmp = tempfile::tempdir().unwrap();
+    write_project(
+        tmp.path(),
+        "cache = \"dist/cache\"\n\n[build]\nsources = \"docs\"\n\n[resolve]\nbudget = 4096\n",
+    );
+
+    let build = context_bin()
+        .arg("build")
+        .current_dir(tmp.path())
+        .output()
+        .unwrap();
+    assert!(build.status.success());
+
+    let resolve = context_bin()
+        .args(["resolve", "--query", "deployment", "--budget", "0"])
+        .current_dir(tmp.path())
+        .output()
+        .unwrap();
+    assert!(resolve.status.success());
+    let output: serde_json::Value = serde_json::from_slice(&resolve.stdout).unwrap();
+    assert_eq!(output["selection"]["budget"].as_u64().unwrap(), 0);
+}
+
+#[test]
+fn config_show_prints_effective_configuration() {
+    let tmp = tempfile::tempdir().unwrap();
+    write_project(tmp.path(), "[build]\nsources = \"docs\"\nformats = [\"md\", \"txt\"]\n");
+
+    let show = context_bin()
+        .arg("--config")
+        .arg(tmp.path().join("context.toml"))
+        .args(["config", "show"])
+        .output()
+        .unwrap();
+    assert!(
+        show.status.success(),
+        "config show failed: {}",
+        String::from_utf8_lossy(&show.stderr)
+    );
+
+    let output: serde_json::Value = serde_json::from_slice(&show.stdout).unwrap();
+    assert_eq!(
+        output["build"]["sources"].as_str().unwrap(),
+        tmp.path().join("docs").to_str().unwrap()
+    );
+    assert_eq!(output["build"]["formats"], serde_json::json!(["md", "txt"]));
+    assert_eq!(output["build"]["unknown_metadata"].as_str().unwrap(), "keep");
+    assert_eq!(output["resolve"]["format"].as_str().unwrap(), "json");
+    assert!(output["resolve"]["budget"].is_null());
+}
+
+#[test]
+fn config_supplies_candidate_watch_and_listing_options() {
+    let tmp = tempfile::tempdir().unwrap();
+    write_project(
+        tmp.path(),
+        "cache = \"dist/cache\"\n\n\
+         [build]\nsources = \"docs\"\nreport = \"dist/report.json\"\ndebounce = 500\n\
+         queries = \"queries.jsonl\"\n\n\
+         [resolve]\nbudget = 4096\nfilters = [\"audience!=internal\"]\npath_prefixes = [\"api\"]\n\
+         exclude_ids = [\"deploy\"]\n\n\
+         [inspect]\ndeep = true\nsort = \"size\"\nprefix = \"api\"\n",
+    );
+
+    let show = context_bin()
+        .args(["config", "show"])
+        .current_dir(tmp.path())
+        .output()
+        .unwrap();
+    assert!(
+        show.status.success(),
+        "config show failed: {}",
+        String::from_utf8_lossy(&show.stderr)
+    );
+    let output: serde_json::Value = serde_json::from_slice(&show.stdout).unwrap();
+    assert_eq!(
+        output["build"]["report"].as_str().unwrap(),
+        tmp.path().join("dist/report.json").to_str().unwrap()
+    );
+    assert_eq!(output["build"]["debounce"].as_u64().unwrap(), 500);
+    assert_eq!(
+        output["build"]["queries"].as_str().unwrap(),
+        tmp.path().join("queries.jsonl").to_str().unwrap()
+    );
+    assert_eq!(output["resolve"]["filters"], serde_json::json!(["audience!=internal"]));
+    assert_eq!(output["resolve"]["path_prefixes"], serde_json::json!(["api"]));
+    assert_eq!(output["resolve"]["exclude_ids"], serde_json::json!(["deploy"]));
+    assert_eq!(output["inspect"]["deep"], serde_json::json!(true));
+    assert_eq!(output["inspect"]["sort"].as_str().unwrap(), "size");
+    assert_eq!(output["inspect"]["prefix"].as_str().unwrap(), "api");
+
+    let build = context_bin()
+        .arg("build")
+        .current_dir(tmp.path())
+        .output()
+        .unwrap();
+    assert!(build.status.success());
+    assert!(tmp.path().join("dist/report.json").is_file());
+
+    let resolve = context_bin()
+        .args(["resolve", "--query", "reference"])
+        .current_dir(tmp.path())
+        .output()
+        .unwrap();
+    assert!(resolve.status.success());
+    let output: serde_json::Value = serde_json::from_slice(&resolve.stdout).unwrap();
+    assert!(output["selection"]["filters"].is_object());
+    for doc in output["documents"].as_array().unwrap() {
+        assert_eq!(doc["source"].as_str().unwrap(), "api.md");
+    }
+
+    // `deep` applies to the summary and `prefix` to the listing only
+    for command in ["inspect", "ls"] {
+        let inspect = context_bin()
+            .arg(command)
+            .current_dir(tmp.path())
+            .output()
+            .unwrap();
+        assert!(
+            inspect.status.success(),
+            "{command} failed: {}",
+            String::from_utf8_lossy(&inspect.stderr)
+        );
+    }
+}
+
+#[test]
+fn invalid_config_returns_exit_code_1() {
+    let tmp = tempfile::tempdir().unwrap();
+    write_project(tmp.path(), "[build]\nsourcez = \"docs\"\n");
+
+    let output = context_bin()
+        .args(["config", "show"])
+        .current_dir(tmp.path())
+        .output()
+        .unwrap();
+
+    assert_eq!(
+        output.status.code().unwrap(),
+        USAGE_ERROR,
+        "Unknown config key should return exit code {}. stderr: {}",
+        USAGE_ERROR,
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn commands_without_config_ignore_invalid_config() {
+    let clean = tempfile::tempdir().unwrap();
+    write_project(clean.path(), "");
+    let cache = clean.path().join("cache");
+    let build = context_bin()
+        .args(["build", "--sources", "docs", "--cache"])
+        .arg(&cache)
+        .current_dir(clean.path())
+        .output()
+        .unwrap();
+    assert!(build.status.success());
+
+    let project = tempfile::tempdir().unwrap();
+    write_project(project.path(), "[build]\nsourcez = \"docs\"\n");
+    for args in [["diff"], ["diff-resolve"]] {
+        let mut command = context_bin();
+        command.args(args).arg(&cache).arg(&cache);
+        if args[0] == "diff-resolve" {
+            command.args(["--query", "deployment", "--budget", "4096"]);
+        }
+        let output = command.current_dir(project.path()).output().unwrap();
+        assert!(
+            output.status.success(),
+            "{} must not read context.toml: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+}
+
+#[test]
+fn config_chunk_by_satisfies_max_chunk_tokens() {
+    let tmp = tempfile::tempdir().unwrap();
+    write_project(
+        tmp.path(),
+        "[build]\nsources = \"docs\"\ncache = \"cache\"\nchunk_by = \"heading\"\n",
+    );
+
+    let build = context_bin()
+        .args(["build", "--max-chunk-tokens", "100"])
+        .current_dir(tmp.path())
+        .output()
+        .unwrap();
+    assert!(
+        build.status.success(),
+        "chunk_by from context.toml must satisfy --max-chunk-tokens: {}",
+        String::from_utf8_lossy(&build.stderr)
+    );
+
+    let output = context_bin()
+        .args(["build", "--cache", "other", "--debounce", "50"])
+        .current_dir(tmp.path())
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code().unwrap(), USAGE_ERROR);
+}
+
+#[test]
+fn cat_uses_inspect_section_cache() {
+    let tmp = tempfile::tempdir().unwrap();
+    write_project(
+        tmp.path(),
+        "[build]\nsources = \"docs\"\ncache = \"built\"\n\n[inspect]\ncache = \"built\"\n",
+    );
+    let build = context_bin()
+        .arg("build")
+        .current_dir(tmp.path())
+        .output()
+        .unwrap();
+    assert!(build.status.success());
+
+    let cat = context_bin()
+        .args(["cat", "deploy.md"])
+        .current_dir(tmp.path())
+        .output()
+        .unwrap();
+    assert!(
+        cat.status.success(),
+        "cat must honor [inspect] cache: {}",
+        String::from_utf8_lossy(&cat.stderr)
+    );
+    assert!(String::from_utf8_lossy(&cat.stdout).contains("Deployment guide"));
+}
+
+#[test]
+fn missing_required_argument_returns_exit_code_1() {
+    let tmp = tempfile::tempdir().unwrap();
+
+    let output = context_bin()
+        .args(["resolve", "--query", "test", "--budget", "10"])
+        .current_dir(tmp.path())
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code().unwrap(), USAGE_ERROR);
+}
@@ -260,3 +260,494 @@ fn empty_query_is_valid() {
     assert!(output.get("documents").is_some());
     assert_eq!(output["selection"]["query"].as_str().unwrap(), "");
 }
+
+/// Read every file under `dir` into a map of relative path to bytes.
+fn read_tree(dir: &std::path::Path) -> std::collections::BTreeMap<String, Vec<u8>> {
+    let mut files = std::collections::BTreeMap::new();
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(current) = stack.pop() {
+        for entry in fs::read_dir(&current).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                stack.push(path);
+            } else {
+                let rel = path.strip_prefix(dir).unwrap().to_string_lossy().to_string();
+                files.insert(rel, fs::read(&path).unwrap());
+            }
+        }
+    }
+    files
+}
+
+#[test]
+fn incremental_build_matches_full_build() {
+    let tmp = tempfile::tempdir().unwrap();
+    let sources = tmp.path().join("sources");
+    write_fixture_sources(&sources);
+
+    let cache = tmp.path().join("cache");
+    let initial = context_bin()
+        .args(["build", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(&cache)
+        .output()
+        .unwrap();
+    assert!(initial.status.success());
+
+    // Modify, add, and delete sources
+    fs::write(
+        sources.join("docs/api.md"),
+        "API reference updated with pagination details",
+    )
+    .unwrap();
+    fs::write(sources.join("docs/faq.md"), "Frequently asked questions").unwrap();
+    fs::remove_file(sources.join("docs/quickstart.md")).unwrap();
+
+    let incremental = context_bin()
+        .args(["build", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(&cache)
+        .arg("--incremental")
+        .output()
+        .unwrap();
+    assert!(
+        incremental.status.success(),
+        "incremental build failed: {}",
+        String::from_utf8_lossy(&incremental.stderr)
+    );
+    // deployment.md and architecture.md are unchanged
+    let stderr = String::from_utf8_lossy(&incremental.stderr);
+    assert!(
+        stderr.contains("Built cache: 3 documents (2 reused)"),
+        "got: {stderr}"
+    );
+
+    let fresh = tmp.path().join("fresh");
+    let full = context_bin()
+        .args(["build", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(&fresh)
+        .output()
+        .unwrap();
+    assert!(full.status.success());
+
+    assert_eq!(
+        read_tree(&cache),
+        read_tree(&fresh),
+        "Incremental build must be byte-identical to a from-scratch build"
+    );
+}
+
+#[test]
+fn document_listing_is_deterministic_and_filterable() {
+    let tmp = tempfile::tempdir().unwrap();
+    let sources = tmp.path().join("sources");
+    write_fixture_sources(&sources);
+
+    let cache = tmp.path().join("cache");
+    let build = context_bin()
+        .args(["build", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(&cache)
+        .output()
+        .unwrap();
+    assert!(build.status.success());
+
+    let inspect = context_bin()
+        .args(["inspect", "--documents", "--cache"])
+        .arg(&cache)
+        .output()
+        .unwrap();
+    let ls = context_bin()
+        .args(["ls", "--cache"])
+        .arg(&cache)
+        .output()
+        .unwrap();
+
+    assert!(inspect.status.success());
+    assert!(ls.status.success());
+    assert_eq!(
+        inspect.stdout, ls.stdout,
+        "`ls` must match `inspect --documents` byte for byte"
+    );
+
+    let rows: Vec<serde_json::Value> = String::from_utf8(ls.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(rows.len(), 4);
+    for row in &rows {
+        for key in ["id", "source", "file", "bytes", "token_estimate", "version", "metadata"] {
+            assert!(row.get(key).is_some(), "Missing '{key}' in {row}");
+        }
+    }
+
+    let filtered = context_bin()
+        .args(["ls", "--prefix", "docs/api", "--sort", "size", "--cache"])
+        .arg(&cache)
+        .output()
+        .unwrap();
+    assert!(filtered.status.success());
+    let lines: Vec<&str> = std::str::from_utf8(&filtered.stdout)
+        .unwrap()
+        .lines()
+        .collect();
+    assert_eq!(lines.len(), 1);
+
+    for flag in [["--sort", "size"], ["--prefix", "docs/api"]] {
+        let summary = context_bin()
+            .arg("inspect")
+            .args(flag)
+            .arg("--cache")
+            .arg(&cache)
+            .output()
+            .unwrap();
+        assert_eq!(
+            summary.status.code(),
+            Some(1),
+            "{} without --documents must be rejected",
+            flag[0]
+        );
+    }
+}
+
+#[test]
+fn cat_prints_stored_content_by_id_and_source() {
+    let tmp = tempfile::tempdir().unwrap();
+    let sources = tmp.path().join("sources");
+    write_fixture_sources(&sources);
+
+    let cache = tmp.path().join("cache");
+    let build = context_bin()
+        .args(["build", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(&cache)
+        .output()
+        .unwrap();
+    assert!(build.status.success());
+
+    let by_source = context_bin()
+        .args(["cat", "--cache"])
+        .arg(&cache)
+        .arg("docs/quickstart.md")
+        .output()
+        .unwrap();
+    assert!(
+        by_source.status.success(),
+        "cat by source failed: {}",
+        String::from_utf8_lossy(&by_source.stderr)
+    );
+    assert!(String::from_utf8_lossy(&by_source.stdout)
+        .contains("Getting started with context resolve in five minutes"));
+
+    let raw = context_bin()
+        .args(["cat", "--raw", "--cache"])
+        .arg(&cache)
+        .arg("docs/quickstart.md")
+        .output()
+        .unwrap();
+    assert!(raw.status.success());
+    let stored: serde_json::Value = serde_json::from_slice(&raw.stdout).unwrap();
+    let id = stored["id"].as_str().unwrap().to_string();
+
+    let by_id = context_bin()
+        .args(["cat", "--cache"])
+        .arg(&cache)
+        .arg(&id)
+        .output()
+        .unwrap();
+    assert!(by_id.status.success());
+    assert_eq!(by_id.stdout, by_source.stdout);
+}
+
+#[test]
+fn batch_resolve_matches_single_query_output() {
+    let tmp = tempfile::tempdir().unwrap();
+    let sources = tmp.path().join("sources");
+    write_fixture_sources(&sources);
+
+    let cache = tmp.path().join("cache");
+    let build = context_bin()
+        .args(["build", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(&cache)
+        .output()
+        .unwrap();
+    assert!(build.status.success());
+
+    let queries = tmp.path().join("queries.jsonl");
+    fs::write(
+        &queries,
+        "{\"id\": 1, \"query\": \"deployment\", \"budget\": 4096}\n\
+         \n\
+         {\"id\": 2, \"query\": \"architecture\"}\n\
+         {\"id\": 3, \"query\": \"\", \"budget\": 0}\n",
+    )
+    .unwrap();
+
+    let batch = context_bin()
+        .args(["resolve", "--cache"])
+        .arg(&cache)
+        .arg("--queries")
+        .arg(&queries)
+        .args(["--budget", "2048"])
+        .output()
+        .unwrap();
+    assert!(
+        batch.status.success(),
+        "batch resolve failed: {}",
+        String::from_utf8_lossy(&batch.stderr)
+    );
+
+    let mut expected = Vec::new();
+    for (query, budget) in [("deployment", "4096"), ("architecture", "2048"), ("", "0")] {
+        let single = context_bin()
+            .args(["resolve", "--cache"])
+            .arg(&cache)
+            .args(["--query", query, "--budget", budget])
+            .output()
+            .unwrap();
+        assert!(single.status.success());
+        expected.extend_from_slice(&single.stdout);
+    }
+
+    assert_eq!(
+        batch.stdout, expected,
+        "Each batch line must be byte-identical to single-query output"
+    );
+}
+
+#[test]
+fn explain_covers_every_document_without_changing_result() {
+    let tmp = tempfile::tempdir().unwrap();
+    let sources = tmp.path().join("sources");
+    write_fixture_sources(&sources);
+
+    let cache = tmp.path().join("cache");
+    let build = context_bin()
+        .args(["build", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(&cache)
+        .output()
+        .unwrap();
+    assert!(build.status.success());
+
+    for budget in ["0", "4096"] {
+        let plain = context_bin()
+            .args(["resolve", "--cache"])
+            .arg(&cache)
+            .args(["--query", "deployment", "--budget", budget])
+            .output()
+            .unwrap();
+        let explained = context_bin()
+            .args(["resolve", "--cache"])
+            .arg(&cache)
+            .args(["--query", "deployment", "--budget", budget, "--explain"])
+            .output()
+            .unwrap();
+        assert!(
+            explained.status.success(),
+            "resolve --explain failed: {}",
+            String::from_utf8_lossy(&explained.stderr)
+        );
+
+        let plain: serde_json::Value = serde_json::from_slice(&plain.stdout).unwrap();
+        let explained: serde_json::Value = serde_json::from_slice(&explained.stdout).unwrap();
+        assert_eq!(explained["result"], plain, "--explain must not alter the result");
+
+        let candidates = explained["explain"]["candidates"].as_array().unwrap();
+        assert_eq!(candidates.len(), 4, "Every cached document must be explained");
+        let selected = plain["documents"].as_array().unwrap().len();
+        let included = candidates
+            .iter()
+            .filter(|c| c["included"].as_bool().unwrap())
+            .count();
+        assert_eq!(included, selected);
+        for candidate in candidates {
+            assert!(candidate["reason"].is_string(), "Missing reason: {candidate}");
+        }
+    }
+}
+
+#[test]
+fn explain_reasons_follow_the_budget() {
+    let tmp = tempfile::tempdir().unwrap();
+    let sources = tmp.path().join("sources");
+    fs::create_dir_all(&sources).unwrap();
+    let big = "Deployment handbook covering every rollout stage in detail. ".repeat(40);
+    let files = [
+        ("big.md", big.as_str()),
+        ("glossary.md", "Glossary of terms."),
+        ("ledger.md", "Ledger reconciliation."),
+        ("tie-a.md", "Deployment checklist."),
+        ("tie-b.md", "Deployment checklist."),
+    ];
+    for (name, content) in files {
+        fs::write(sources.join(name), content).unwrap();
+    }
+    let cache = tmp.path().join("cache");
+    let build = context_bin()
+        .args(["build", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(&cache)
+        .output()
+        .unwrap();
+    assert!(build.status.success());
+
+    let explain = |budget: u64, pins: &[&str]| -> Vec<serde_json::Value> {
+        let pins = pins.iter().flat_map(|pin| ["--pin", *pin]);
+        let output = context_bin()
+            .args(["resolve", "--cache"])
+            .arg(&cache)
+            .args(["--query", "deployment", "--explain"])
+            .args(["--budget", &budget.to_string()])
+            .args(pins)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let output: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        output["explain"]["candidates"].as_array().unwrap().clone()
+    };
+    let find = |candidates: &[serde_json::Value], id_part: &str| {
+        candidates
+            .iter()
+            .find(|c| c["id"].as_str().unwrap().contains(id_part))
+            .unwrap()
+            .clone()
+    };
+
+    // Room for the pin and one of the two tied documents, not the big one
+    let all = explain(1_000_000, &[]);
+    let tokens = |id_part: &str| find(&all, id_part)["tokens"].as_u64().unwrap();
+    let glossary = find(&all, "glossary")["id"].as_str().unwrap().to_string();
+    assert!(tokens("big") > tokens("tie-a"));
+    let candidates = explain(tokens("glossary") + tokens("tie-a"), &[&glossary]);
+
+    let reason = |id_part: &str| find(&candidates, id_part)["reason"].clone();
+    assert_eq!(reason("glossary"), "pinned");
+    assert_eq!(reason("ledger"), "below_threshold");
+    assert_eq!(reason("big"), "exceeds_remaining_budget");
+    assert_eq!(reason("tie-a"), "tie_broken");
+    assert_eq!(reason("tie-b"), "tie_broken");
+
+    // Without room for either tied document, the budget decides both
+    let candidates = explain(tokens("glossary"), &[&glossary]);
+    for candidate in candidates.iter().filter(|c| c["rank"].is_u64()) {
+        assert_eq!(candidate["reason"], "exceeds_remaining_budget", "{candidate}");
+    }
+}
+
+#[test]
+fn query_file_and_stdin_match_inline_query() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let tmp = tempfile::tempdir().unwrap();
+    let sources = tmp.path().join("sources");
+    write_fixture_sources(&sources);
+
+    let cache = tmp.path().join("cache");
+    let build = context_bin()
+        .args(["build", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(&cache)
+        .output()
+        .unwrap();
+    assert!(build.status.success());
+
+    let inline = context_bin()
+        .args(["resolve", "--cache"])
+        .arg(&cache)
+        .args(["--query", "deployment architecture", "--budget", "4096"])
+        .output()
+        .unwrap();
+    assert!(inline.status.success());
+
+    let query_file = tmp.path().join("query.txt");
+    fs::write(&query_file, "deployment architecture\n").unwrap();
+    let from_file = context_bin()
+        .args(["resolve", "--cache"])
+        .arg(&cache)
+        .arg("--query-file")
+        .arg(&query_file)
+        .args(["--budget", "4096"])
+        .output()
+        .unwrap();
+    assert_eq!(from_file.stdout, inline.stdout);
+
+    let mut child = context_bin()
+        .args(["resolve", "--cache"])
+        .arg(&cache)
+        .args(["--query", "-", "--budget", "4096"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"deployment architecture\n")
+        .unwrap();
+    let from_stdin = child.wait_with_output().unwrap();
+    assert_eq!(from_stdin.stdout, inline.stdout);
+}
+
+#[test]
+fn query_normalization_is_reported_in_selection_block() {
+    let tmp = tempfile::tempdir().unwrap();
+    let sources = tmp.path().join("sources");
+    write_fixture_sources(&sources);
+
+    let cache = tmp.path().join("cache");
+    let build = context_bin()
+        .args(["build", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(&cache)
+        .output()
+        .unwrap();
+    assert!(build.status.success());
+
+    let resolve = |query: &str| {
+        let output = context_bin()
+            .args(["resolve", "--cache"])
+            .arg(&cache)
+            .args(["--query", query, "--budget", "4096"])
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "resolve failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+    };
+
+    let clean = resolve("cafe\u{301} deployment");
+    let messy = resolve("  cafe\u{301}\t\n deployment ");
+    let normalization = &messy["selection"]["query_normalization"];
+    assert_eq!(normalization["original"], "  cafe\u{301}\t\n deployment ");
+    assert_eq!(normalization["normalized"], "caf\u{e9} deployment");
+    assert_eq!(
+        normalization["rules"],
+        serde_json::json!(["unicode_nfc", "whitespace_collapse"])
+    );
+    assert_eq!(messy["documents"], clean["documents"]);
+
+    let plain = resolve("deployment");
+    assert!(
+        plain["selection"].get("query_normalization").is_none(),
+        "Queries needing no normalization must leave the output unchanged"
+    );
+}
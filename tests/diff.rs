@@ -0,0 +1,195 @@
+//! Cache comparison tests.
+//!
+//! Validates that `context diff` reports document-level changes between
+//! two caches and signals differences through its exit code on request.
+
+use std::fs;
+
+mod common;
+
+use common::context_bin;
+
+const DIFFERS: i32 = 1;
+
+fn build(sources: &std::path::Path, cache: &std::path::Path) {
+    let output = context_bin()
+        .args(["build", "--sources"])
+        .arg(sources)
+        .arg("--cache")
+        .arg(cache)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "build failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// Build two caches: `old` from the initial sources, `new` after editing,
+/// adding, and removing one document each.
+fn build_old_and_new(tmp: &std::path::Path) -> (std::path::PathBuf, std::path::PathBuf) {
+    let sources = tmp.join("sources");
+    fs::create_dir_all(&sources).unwrap();
+    fs::write(sources.join("keep.md"), "Unchanged document").unwrap();
+    fs::write(sources.join("edit.md"), "Deploy with the blue pipeline\n").unwrap();
+    fs::write(sources.join("drop.md"), "Obsolete document").unwrap();
+    let old = tmp.join("old");
+    build(&sources, &old);
+
+    fs::write(sources.join("edit.md"), "Deploy with the green pipeline\n").unwrap();
+    fs::write(sources.join("new.md"), "Brand new document").unwrap();
+    fs::remove_file(sources.join("drop.md")).unwrap();
+    let new = tmp.join("new");
+    build(&sources, &new);
+
+    (old, new)
+}
+
+#[test]
+fn diff_reports_added_removed_and_modified_documents() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (old, new) = build_old_and_new(tmp.path());
+
+    let output = context_bin()
+        .arg("diff")
+        .arg(&old)
+        .arg(&new)
+        .arg("--content")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "diff failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["added"].as_array().unwrap().len(), 1);
+    assert_eq!(report["removed"].as_array().unwrap().len(), 1);
+    assert_eq!(report["modified"].as_array().unwrap().len(), 1);
+    assert_eq!(report["document_count_delta"].as_i64().unwrap(), 0);
+    assert_eq!(report["identical"].as_bool().unwrap(), false);
+
+    let diff = report["modified"][0]["diff"].as_str().unwrap();
+    assert!(diff.contains("-Deploy with the blue pipeline"), "got: {diff}");
+    assert!(diff.contains("+Deploy with the green pipeline"), "got: {diff}");
+}
+
+#[test]
+fn diff_exit_code_signals_differences() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (old, new) = build_old_and_new(tmp.path());
+
+    let differs = context_bin()
+        .arg("diff")
+        .arg(&old)
+        .arg(&new)
+        .arg("--exit-code")
+        .output()
+        .unwrap();
+    assert_eq!(differs.status.code().unwrap(), DIFFERS);
+
+    let same = context_bin()
+        .arg("diff")
+        .arg(&new)
+        .arg(&new)
+        .arg("--exit-code")
+        .output()
+        .unwrap();
+    assert!(same.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&same.stdout).unwrap();
+    assert_eq!(report["identical"].as_bool().unwrap(), true);
+}
+
+#[test]
+fn diff_resolve_reports_selection_changes() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (old, new) = build_old_and_new(tmp.path());
+
+    let queries = tmp.path().join("queries.jsonl");
+    fs::write(
+        &queries,
+        "{\"id\": \"new\", \"query\": \"brand new\", \"budget\": 4096}\n\
+         {\"id\": \"obsolete\", \"query\": \"obsolete\"}\n",
+    )
+    .unwrap();
+
+    let output = context_bin()
+        .arg("diff-resolve")
+        .arg(&old)
+        .arg(&new)
+        .arg("--queries")
+        .arg(&queries)
+        .args(["--budget", "4096"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "diff-resolve failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let results = report["queries"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0]["id"].as_str().unwrap(), "new");
+    assert_eq!(results[1]["budget"].as_u64().unwrap(), 4096);
+
+    for result in results {
+        for key in [
+            "entered",
+            "left",
+            "moved",
+            "changed",
+            "old_tokens",
+            "new_tokens",
+            "token_delta",
+        ] {
+            assert!(result.get(key).is_some(), "Missing '{key}' in {result}");
+        }
+    }
+}
+
+#[test]
+fn diff_resolve_same_cache_is_unchanged() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (_, new) = build_old_and_new(tmp.path());
+
+    let output = context_bin()
+        .arg("diff-resolve")
+        .arg(&new)
+        .arg(&new)
+        .args(["--query", "deploy", "--budget", "4096", "--exit-code"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["changed_queries"].as_u64().unwrap(), 0);
+    assert_eq!(report["queries"][0]["unchanged"].as_bool().unwrap(), true);
+}
+
+#[test]
+fn diff_resolve_reports_content_changes_at_the_same_rank() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (old, new) = build_old_and_new(tmp.path());
+
+    let output = context_bin()
+        .arg("diff-resolve")
+        .arg(&old)
+        .arg(&new)
+        .args(["--query", "pipeline", "--budget", "4096", "--exit-code"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code().unwrap(), DIFFERS, "Edited content must count as a change");
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let query = &report["queries"][0];
+    assert_eq!(query["unchanged"], false);
+    assert_eq!(query["entered"], serde_json::json!([]));
+    assert_eq!(query["left"], serde_json::json!([]));
+    let changed = query["changed"].as_array().unwrap();
+    assert_eq!(changed.len(), 1);
+    assert_ne!(changed[0]["old_version"], changed[0]["new_version"]);
+}
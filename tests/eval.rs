@@ -0,0 +1,103 @@
+//! Relevance evaluation tests.
+//!
+//! Validates `context eval` metrics against hand-labeled judgments and the
+//! `--min-recall` quality gate.
+
+use std::fs;
+
+mod common;
+
+use common::context_bin;
+
+const THRESHOLD_FAILED: i32 = 1;
+
+/// Build a fixture cache and return it with the ID of the deployment doc.
+fn build_cache(tmp: &std::path::Path) -> (std::path::PathBuf, String) {
+    let cache = common::build_cache(
+        tmp,
+        "cache",
+        &[
+            ("deployment.md", "Deployment guide for production environments"),
+            ("api.md", "API reference for REST endpoints"),
+        ],
+    );
+
+    let raw = context_bin()
+        .args(["cat", "--raw", "--cache"])
+        .arg(&cache)
+        .arg("deployment.md")
+        .output()
+        .unwrap();
+    let stored: serde_json::Value = serde_json::from_slice(&raw.stdout).unwrap();
+    (cache, stored["id"].as_str().unwrap().to_string())
+}
+
+#[test]
+fn eval_reports_per_query_and_aggregate_metrics() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (cache, deployment_id) = build_cache(tmp.path());
+
+    let judgments = tmp.path().join("judgments.jsonl");
+    fs::write(
+        &judgments,
+        format!(
+            "{{\"id\": \"deploy\", \"query\": \"deployment production\", \"relevant\": [\"{deployment_id}\"]}}\n"
+        ),
+    )
+    .unwrap();
+
+    let output = context_bin()
+        .args(["eval", "--cache"])
+        .arg(&cache)
+        .arg("--judgments")
+        .arg(&judgments)
+        .args(["--budget", "4096"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "eval failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["query_count"].as_u64().unwrap(), 1);
+    let metrics = &report["queries"][0]["metrics"];
+    assert_eq!(metrics["recall"].as_f64().unwrap(), 1.0);
+    for key in ["recall", "precision", "mrr", "ndcg"] {
+        assert!(report["aggregate"][key].is_number(), "Missing aggregate '{key}'");
+    }
+}
+
+#[test]
+fn eval_min_recall_gate_fails() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (cache, _) = build_cache(tmp.path());
+
+    let judgments = tmp.path().join("judgments.jsonl");
+    fs::write(
+        &judgments,
+        "{\"query\": \"deployment\", \"budget\": 0, \"relevant\": [\"missing-doc\"]}\n",
+    )
+    .unwrap();
+
+    let output = context_bin()
+        .args(["eval", "--cache"])
+        .arg(&cache)
+        .arg("--judgments")
+        .arg(&judgments)
+        .args(["--min-recall", "0.5", "--format", "table"])
+        .output()
+        .unwrap();
+
+    assert_eq!(
+        output.status.code().unwrap(),
+        THRESHOLD_FAILED,
+        "Recall below --min-recall should return exit code {}. stderr: {}",
+        THRESHOLD_FAILED,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let table = String::from_utf8_lossy(&output.stdout);
+    assert!(table.starts_with("query"), "got: {table}");
+    assert!(table.contains("mean"));
+}
@@ -11,6 +11,9 @@ fn context_bin() -> Command {
 }
 
 // Exit code constants (frozen per cli_spec.md)
+const USAGE_ERROR: i32 = 1;
+const INVALID_QUERY: i32 = 2;
+const INVALID_BUDGET: i32 = 3;
 const CACHE_MISSING: i32 = 4;
 const CACHE_INVALID: i32 = 5;
 const IO_ERROR: i32 = 6;
@@ -194,3 +197,304 @@ fn stderr_contains_error_prefix() {
         stderr
     );
 }
+
+/// Build a one-document cache and return its path and the stored document file.
+fn build_single_document_cache(tmp: &std::path::Path) -> (std::path::PathBuf, std::path::PathBuf) {
+    let sources = tmp.join("sources");
+    fs::create_dir_all(&sources).unwrap();
+    fs::write(sources.join("doc.md"), "Original document content").unwrap();
+
+    let cache = tmp.join("cache");
+    let build = context_bin()
+        .args(["build", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(&cache)
+        .output()
+        .unwrap();
+    assert!(build.status.success());
+
+    let manifest: serde_json::Value =
+        serde_json::from_slice(&fs::read(cache.join("manifest.json")).unwrap()).unwrap();
+    let file = cache.join(manifest["documents"][0]["file"].as_str().unwrap());
+    (cache, file)
+}
+
+#[test]
+fn inspect_deep_tampered_document_returns_exit_code_5() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (cache, file) = build_single_document_cache(tmp.path());
+
+    let mut stored: serde_json::Value = serde_json::from_slice(&fs::read(&file).unwrap()).unwrap();
+    stored["content"] = serde_json::json!("Tampered document content");
+    fs::write(&file, serde_json::to_vec(&stored).unwrap()).unwrap();
+
+    let output = context_bin()
+        .args(["inspect", "--deep", "--cache"])
+        .arg(&cache)
+        .output()
+        .unwrap();
+
+    assert_eq!(
+        output.status.code().unwrap(),
+        CACHE_INVALID,
+        "Tampered document should return exit code {}. stderr: {}",
+        CACHE_INVALID,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["valid"].as_bool().unwrap(), false);
+    assert_eq!(
+        report["verification"]["failures"].as_array().unwrap().len(),
+        1
+    );
+}
+
+#[test]
+fn inspect_deep_orphaned_file_returns_exit_code_5() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (cache, _) = build_single_document_cache(tmp.path());
+    fs::write(cache.join("stray.json"), "{}").unwrap();
+
+    let output = context_bin()
+        .args(["inspect", "--deep", "--cache"])
+        .arg(&cache)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code().unwrap(), CACHE_INVALID);
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        report["verification"]["orphaned_files"],
+        serde_json::json!(["stray.json"])
+    );
+}
+
+#[test]
+fn inspect_deep_intact_cache_succeeds() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (cache, _) = build_single_document_cache(tmp.path());
+
+    let output = context_bin()
+        .args(["inspect", "--deep", "--cache"])
+        .arg(&cache)
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "Deep inspect of an intact cache should succeed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["valid"].as_bool().unwrap(), true);
+    assert_eq!(
+        report["verification"]["cache_version_matches"].as_bool().unwrap(),
+        true
+    );
+}
+
+#[test]
+fn cat_unknown_document_returns_exit_code_1() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (cache, _) = build_single_document_cache(tmp.path());
+
+    let output = context_bin()
+        .args(["cat", "--cache"])
+        .arg(&cache)
+        .arg("no/such/doc.md")
+        .output()
+        .unwrap();
+
+    assert_eq!(
+        output.status.code().unwrap(),
+        USAGE_ERROR,
+        "Unknown document should return exit code {}. stderr: {}",
+        USAGE_ERROR,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Document not found"));
+}
+
+#[test]
+fn non_utf8_query_file_returns_exit_code_2() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (cache, _) = build_single_document_cache(tmp.path());
+
+    let query_file = tmp.path().join("query.txt");
+    fs::write(&query_file, [b'd', b'e', 0xff, 0xfe]).unwrap();
+
+    let output = context_bin()
+        .args(["resolve", "--cache"])
+        .arg(&cache)
+        .arg("--query-file")
+        .arg(&query_file)
+        .args(["--budget", "1000"])
+        .output()
+        .unwrap();
+
+    assert_eq!(
+        output.status.code().unwrap(),
+        INVALID_QUERY,
+        "Non-UTF-8 query file should return exit code {}. stderr: {}",
+        INVALID_QUERY,
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn non_utf8_stdin_query_returns_exit_code_2() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let tmp = tempfile::tempdir().unwrap();
+    let (cache, _) = build_single_document_cache(tmp.path());
+
+    let mut child = context_bin()
+        .args(["resolve", "--cache"])
+        .arg(&cache)
+        .args(["--query", "-", "--budget", "1000"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(&[0xc3, 0x28]).unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert_eq!(
+        output.status.code().unwrap(),
+        INVALID_QUERY,
+        "Non-UTF-8 stdin query should return exit code {}. stderr: {}",
+        INVALID_QUERY,
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+fn resolve_exit_code(cache: &std::path::Path, query: &str) -> i32 {
+    let output = context_bin()
+        .args(["resolve", "--cache"])
+        .arg(cache)
+        .args(["--query", query, "--budget", "1000"])
+        .output()
+        .unwrap();
+    output.status.code().unwrap()
+}
+
+#[test]
+fn control_character_query_returns_exit_code_2() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (cache, _) = build_single_document_cache(tmp.path());
+
+    for query in ["bell\u{7}", "escape\u{1b}[0m", "delete\u{7f}"] {
+        assert_eq!(
+            resolve_exit_code(&cache, query),
+            INVALID_QUERY,
+            "Query {query:?} with a control character should return exit code {INVALID_QUERY}"
+        );
+    }
+}
+
+#[test]
+fn nul_in_query_file_returns_exit_code_2() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (cache, _) = build_single_document_cache(tmp.path());
+
+    let query_file = tmp.path().join("query.txt");
+    fs::write(&query_file, b"before\0after").unwrap();
+
+    let output = context_bin()
+        .args(["resolve", "--cache"])
+        .arg(&cache)
+        .arg("--query-file")
+        .arg(&query_file)
+        .args(["--budget", "1000"])
+        .output()
+        .unwrap();
+
+    assert_eq!(
+        output.status.code().unwrap(),
+        INVALID_QUERY,
+        "Query containing NUL should return exit code {}. stderr: {}",
+        INVALID_QUERY,
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn overlong_query_returns_exit_code_2() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (cache, _) = build_single_document_cache(tmp.path());
+
+    assert_eq!(
+        resolve_exit_code(&cache, &"a".repeat(4097)),
+        INVALID_QUERY,
+        "Query over the length limit should return exit code {INVALID_QUERY}"
+    );
+    assert_eq!(resolve_exit_code(&cache, &"a".repeat(4096)), 0);
+}
+
+#[test]
+fn invalid_query_in_query_file_returns_exit_code_2() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (cache, _) = build_single_document_cache(tmp.path());
+
+    let queries = tmp.path().join("queries.jsonl");
+    fs::write(&queries, "{\"query\": \"ok\"}\n{\"query\": \"bad\\u0000\"}\n").unwrap();
+
+    let output = context_bin()
+        .args(["resolve", "--cache"])
+        .arg(&cache)
+        .arg("--queries")
+        .arg(&queries)
+        .args(["--budget", "1000"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code().unwrap(), INVALID_QUERY);
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains(":2:"),
+        "Error should name the offending line"
+    );
+}
+
+#[test]
+fn pins_exceeding_budget_return_exit_code_3() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (cache, _) = build_single_document_cache(tmp.path());
+
+    let manifest: serde_json::Value =
+        serde_json::from_slice(&fs::read(cache.join("manifest.json")).unwrap()).unwrap();
+    let id = manifest["documents"][0]["id"].as_str().unwrap();
+
+    let output = context_bin()
+        .args(["resolve", "--cache"])
+        .arg(&cache)
+        .args(["--query", "document", "--budget", "1", "--pin", id])
+        .output()
+        .unwrap();
+
+    assert_eq!(
+        output.status.code().unwrap(),
+        INVALID_BUDGET,
+        "Pins over the budget should return exit code {}. stderr: {}",
+        INVALID_BUDGET,
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn unknown_pin_returns_exit_code_1() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (cache, _) = build_single_document_cache(tmp.path());
+
+    let output = context_bin()
+        .args(["resolve", "--cache"])
+        .arg(&cache)
+        .args(["--query", "document", "--budget", "1000", "--pin", "no-such-doc"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code().unwrap(), USAGE_ERROR);
+}
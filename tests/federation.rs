@@ -0,0 +1,161 @@
+//! Federated resolve tests.
+//!
+//! Validates that `resolve` with several `--cache` options selects from
+//! one pool under one budget, prefixes IDs with cache aliases, and is
+//! deterministic.
+
+use std::fs;
+
+mod common;
+
+use common::{build_cache, context_bin};
+
+const USAGE_ERROR: i32 = 1;
+
+fn team_caches(dir: &std::path::Path) -> (std::path::PathBuf, std::path::PathBuf) {
+    let platform = build_cache(
+        dir,
+        "platform",
+        &[("deploy.md", "Deployment pipeline for platform services")],
+    );
+    let payments = build_cache(
+        dir,
+        "payments",
+        &[
+            ("deploy.md", "Deployment checklist for payments"),
+            ("ledger.md", "Ledger reconciliation process"),
+        ],
+    );
+    (platform, payments)
+}
+
+fn resolve(args: &[&std::ffi::OsStr]) -> std::process::Output {
+    context_bin()
+        .arg("resolve")
+        .args(args)
+        .args(["--query", "deployment", "--budget", "4096"])
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn federated_resolve_prefixes_ids_and_records_origin() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (platform, payments) = team_caches(tmp.path());
+
+    let output = resolve(&[
+        "--cache".as_ref(),
+        platform.as_os_str(),
+        "--cache".as_ref(),
+        payments.as_os_str(),
+    ]);
+    assert!(
+        output.status.success(),
+        "federated resolve failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let documents = result["documents"].as_array().unwrap();
+    assert!(documents.len() >= 2, "Both deployment docs should be selected");
+    for doc in documents {
+        let id = doc["id"].as_str().unwrap();
+        let cache = doc["cache"].as_str().unwrap();
+        assert!(
+            id.starts_with(&format!("{cache}/")),
+            "ID {id} should be prefixed with its cache alias {cache}"
+        );
+    }
+    let aliases: Vec<&str> = result["selection"]["caches"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["alias"].as_str().unwrap())
+        .collect();
+    assert_eq!(aliases, ["platform", "payments"]);
+}
+
+#[test]
+fn federated_resolve_is_deterministic_and_honors_explicit_aliases() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (platform, payments) = team_caches(tmp.path());
+
+    let platform_arg = format!("plat={}", platform.display());
+    let payments_arg = format!("pay={}", payments.display());
+    let args: [&std::ffi::OsStr; 4] = [
+        "--cache".as_ref(),
+        platform_arg.as_ref(),
+        "--cache".as_ref(),
+        payments_arg.as_ref(),
+    ];
+
+    let first = resolve(&args);
+    let second = resolve(&args);
+    assert!(first.status.success());
+    assert_eq!(first.stdout, second.stdout, "Federated output must be deterministic");
+
+    let result: serde_json::Value = serde_json::from_slice(&first.stdout).unwrap();
+    for doc in result["documents"].as_array().unwrap() {
+        let cache = doc["cache"].as_str().unwrap();
+        assert!(cache == "plat" || cache == "pay", "unexpected alias {cache}");
+    }
+}
+
+#[test]
+fn duplicate_cache_alias_returns_exit_code_1() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (platform, _) = team_caches(tmp.path());
+
+    let output = resolve(&[
+        "--cache".as_ref(),
+        platform.as_os_str(),
+        "--cache".as_ref(),
+        platform.as_os_str(),
+    ]);
+    assert_eq!(
+        output.status.code().unwrap(),
+        USAGE_ERROR,
+        "Duplicate aliases should return exit code {}",
+        USAGE_ERROR
+    );
+}
+
+#[test]
+fn federated_resolve_picks_up_a_rebuilt_cache() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (platform, payments) = team_caches(tmp.path());
+    let args: [&std::ffi::OsStr; 4] = [
+        "--cache".as_ref(),
+        platform.as_os_str(),
+        "--cache".as_ref(),
+        payments.as_os_str(),
+    ];
+    let platform_count = |output: &std::process::Output| {
+        assert!(output.status.success());
+        let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        result["documents"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|doc| doc["cache"] == "platform")
+            .count()
+    };
+    assert_eq!(platform_count(&resolve(&args)), 1);
+
+    let sources = tmp.path().join("platform-sources");
+    fs::write(sources.join("rollout.md"), "Deployment rollout plan").unwrap();
+    let rebuild = context_bin()
+        .args(["build", "--force", "--sources"])
+        .arg(&sources)
+        .arg("--cache")
+        .arg(&platform)
+        .output()
+        .unwrap();
+    assert!(rebuild.status.success());
+
+    assert_eq!(
+        platform_count(&resolve(&args)),
+        2,
+        "A merged cache must not outlive a change to one of its caches"
+    );
+}
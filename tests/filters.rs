@@ -0,0 +1,190 @@
+//! Resolve candidate tests.
+//!
+//! Validates that `--filter`, `--path-prefix`, and `--exclude-id` restrict
+//! the candidate documents and are echoed in the output, and that `--pin`
+//! always includes its documents.
+
+use std::fs;
+
+mod common;
+
+use common::context_bin;
+
+/// Build a cache of deployment docs with differing metadata and paths.
+fn build_cache(tmp: &std::path::Path) -> std::path::PathBuf {
+    let files = [
+        (
+            "runbooks/deploy.md",
+            "---\ntags: [runbook, ops]\naudience: internal\n---\nDeployment runbook steps",
+        ),
+        (
+            "runbooks/rollback.md",
+            "---\ntags: [runbook]\naudience: public\n---\nDeployment rollback procedure",
+        ),
+        ("api/deploy.md", "---\ntags: [api]\n---\nDeployment API endpoints"),
+    ];
+    common::build_cache(tmp, "cache", &files)
+}
+
+/// Resolve "deployment" with `extra` arguments and return the selected
+/// sources (sorted) and the parsed output.
+fn resolve(cache: &std::path::Path, extra: &[&str]) -> (Vec<String>, serde_json::Value) {
+    let output = context_bin()
+        .args(["resolve", "--cache"])
+        .arg(cache)
+        .args(["--query", "deployment", "--budget", "4096"])
+        .args(extra)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "resolve failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    let mut sources = Vec::new();
+    for doc in result["documents"].as_array().unwrap() {
+        let cat = context_bin()
+            .args(["cat", "--raw", "--cache"])
+            .arg(cache)
+            .arg(doc["id"].as_str().unwrap())
+            .output()
+            .unwrap();
+        let stored: serde_json::Value = serde_json::from_slice(&cat.stdout).unwrap();
+        sources.push(stored["source"].as_str().unwrap().replace('\\', "/"));
+    }
+    sources.sort();
+    (sources, result)
+}
+
+#[test]
+fn metadata_filters_restrict_candidates() {
+    let tmp = tempfile::tempdir().unwrap();
+    let cache = build_cache(tmp.path());
+
+    let (sources, _) = resolve(&cache, &["--filter", "tags=runbook"]);
+    assert_eq!(sources, ["runbooks/deploy.md", "runbooks/rollback.md"]);
+
+    let (sources, _) = resolve(
+        &cache,
+        &["--filter", "tags=runbook", "--filter", "audience!=internal"],
+    );
+    assert_eq!(sources, ["runbooks/rollback.md"]);
+}
+
+#[test]
+fn path_prefix_and_exclude_id_restrict_candidates() {
+    let tmp = tempfile::tempdir().unwrap();
+    let cache = build_cache(tmp.path());
+
+    let (sources, result) = resolve(&cache, &["--path-prefix", "runbooks/"]);
+    assert_eq!(sources, ["runbooks/deploy.md", "runbooks/rollback.md"]);
+
+    let excluded = result["documents"][0]["id"].as_str().unwrap().to_string();
+    let (_, result) = resolve(
+        &cache,
+        &["--path-prefix", "runbooks/", "--exclude-id", &excluded],
+    );
+    let ids: Vec<&str> = result["documents"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|doc| doc["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids.len(), 1);
+    assert!(!ids.contains(&excluded.as_str()));
+}
+
+#[test]
+fn applied_filters_are_echoed_in_selection_block() {
+    let tmp = tempfile::tempdir().unwrap();
+    let cache = build_cache(tmp.path());
+
+    let (_, result) = resolve(
+        &cache,
+        &["--filter", "audience!=internal", "--path-prefix", "runbooks/"],
+    );
+    assert_eq!(
+        result["selection"]["filters"],
+        serde_json::json!({
+            "metadata": ["audience!=internal"],
+            "path_prefixes": ["runbooks/"],
+            "exclude_ids": [],
+        })
+    );
+
+    let (_, unfiltered) = resolve(&cache, &[]);
+    assert!(unfiltered["selection"].get("filters").is_none());
+}
+
+#[test]
+fn pinned_document_leads_every_resolve() {
+    let tmp = tempfile::tempdir().unwrap();
+    let cache = build_cache(tmp.path());
+
+    // Pin the API document, which the runbook filter would exclude
+    let (_, unpinned) = resolve(&cache, &["--path-prefix", "api/"]);
+    let pinned_id = unpinned["documents"][0]["id"].as_str().unwrap().to_string();
+
+    let (_, result) = resolve(&cache, &["--pin", &pinned_id, "--filter", "tags=runbook"]);
+    let documents = result["documents"].as_array().unwrap();
+    assert_eq!(documents[0]["id"], pinned_id.as_str());
+    assert_eq!(documents[0]["pinned"], true);
+    assert_eq!(
+        documents.iter().filter(|doc| doc["id"] == pinned_id.as_str()).count(),
+        1,
+        "A pinned document must not also be selected"
+    );
+    assert_eq!(result["selection"]["pinned"]["ids"], serde_json::json!([pinned_id]));
+}
+
+#[test]
+fn config_pins_combine_with_cli_pins() {
+    let tmp = tempfile::tempdir().unwrap();
+    let cache = build_cache(tmp.path());
+
+    let (_, all) = resolve(&cache, &[]);
+    let ids: Vec<String> = all["documents"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|doc| doc["id"].as_str().unwrap().to_string())
+        .collect();
+
+    let config = tmp.path().join("context.toml");
+    fs::write(&config, format!("[resolve]\npins = [\"{}\"]\n", ids[2])).unwrap();
+
+    let output = context_bin()
+        .arg("--config")
+        .arg(&config)
+        .args(["resolve", "--cache"])
+        .arg(&cache)
+        .args(["--query", "deployment", "--budget", "4096", "--pin", &ids[1]])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "resolve failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        result["selection"]["pinned"]["ids"],
+        serde_json::json!([ids[2], ids[1]])
+    );
+}
+
+#[test]
+fn pinned_tokens_match_the_engine_count() {
+    let tmp = tempfile::tempdir().unwrap();
+    let cache = build_cache(tmp.path());
+
+    let (_, unpinned) = resolve(&cache, &["--path-prefix", "api/"]);
+    let selected = &unpinned["documents"][0];
+    let pinned_id = selected["id"].as_str().unwrap();
+
+    let (_, result) = resolve(&cache, &["--pin", pinned_id]);
+    assert_eq!(result["documents"][0]["tokens"], selected["tokens"]);
+    assert_eq!(result["selection"]["pinned"]["tokens"], selected["tokens"]);
+}
@@ -0,0 +1,149 @@
+//! MCP stdio server tests.
+//!
+//! Drives `context mcp` with JSON-RPC messages on stdin and validates that
+//! the `context.resolve` tool returns exactly the CLI's output.
+
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+mod common;
+
+use common::{context_bin, two_doc_cache};
+
+/// Send `messages` (one per line) to a server session and return its
+/// responses.
+fn session(cache: &std::path::Path, messages: &[serde_json::Value]) -> Vec<serde_json::Value> {
+    let mut command = context_bin();
+    command.args(["mcp", "--cache"]).arg(cache);
+    session_of(command, messages)
+}
+
+fn session_of(mut command: Command, messages: &[serde_json::Value]) -> Vec<serde_json::Value> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    {
+        let mut stdin = child.stdin.take().unwrap();
+        for message in messages {
+            writeln!(stdin, "{message}").unwrap();
+        }
+    }
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+fn call(id: u64, arguments: serde_json::Value) -> serde_json::Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": "tools/call",
+        "params": { "name": "context.resolve", "arguments": arguments },
+    })
+}
+
+#[test]
+fn resolve_tool_matches_cli_output() {
+    let tmp = tempfile::tempdir().unwrap();
+    let cache = two_doc_cache(tmp.path());
+
+    let responses = session(
+        &cache,
+        &[
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": { "protocolVersion": "2024-11-05", "capabilities": {} },
+            }),
+            serde_json::json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+            serde_json::json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" }),
+            call(3, serde_json::json!({ "query": "deployment", "budget": 4096 })),
+        ],
+    );
+    assert_eq!(responses.len(), 3, "Notifications must not be answered");
+    assert_eq!(responses[0]["result"]["serverInfo"]["name"], "context");
+    assert_eq!(responses[1]["result"]["tools"][0]["name"], "context.resolve");
+
+    let cli = context_bin()
+        .args(["resolve", "--cache"])
+        .arg(&cache)
+        .args(["--query", "deployment", "--budget", "4096"])
+        .output()
+        .unwrap();
+    let result = &responses[2]["result"];
+    assert_eq!(result["isError"], false);
+    let text = result["content"][0]["text"].as_str().unwrap();
+    assert_eq!(
+        format!("{text}\n").as_bytes(),
+        cli.stdout,
+        "context.resolve must be byte-identical to `context resolve`"
+    );
+}
+
+#[test]
+fn resolve_tool_errors_use_frozen_codes() {
+    let tmp = tempfile::tempdir().unwrap();
+    let cache = two_doc_cache(tmp.path());
+
+    let responses = session(
+        &cache,
+        &[
+            call(1, serde_json::json!({ "query": "bad\u{0}", "budget": 10 })),
+            call(2, serde_json::json!({ "query": "deployment" })),
+            serde_json::json!({ "jsonrpc": "2.0", "id": 3, "method": "resources/list" }),
+        ],
+    );
+
+    for (response, code) in responses[..2].iter().zip(["invalid_query", "invalid_budget"]) {
+        assert_eq!(response["result"]["isError"], true);
+        let text = response["result"]["content"][0]["text"].as_str().unwrap();
+        let error: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(error["error"]["code"], code);
+    }
+    assert_eq!(responses[2]["error"]["code"], -32601);
+}
+
+#[test]
+fn resolve_tool_honors_config_pins() {
+    let tmp = tempfile::tempdir().unwrap();
+    let cache = two_doc_cache(tmp.path());
+
+    let api = context_bin()
+        .args(["resolve", "--cache"])
+        .arg(&cache)
+        .args(["--query", "API reference", "--budget", "4096"])
+        .output()
+        .unwrap();
+    let api: serde_json::Value = serde_json::from_slice(&api.stdout).unwrap();
+    let pinned_id = api["documents"][0]["id"].as_str().unwrap();
+    let config = tmp.path().join("context.toml");
+    fs::write(&config, format!("[resolve]\npins = [\"{pinned_id}\"]\n")).unwrap();
+
+    let mut command = context_bin();
+    command.arg("--config").arg(&config).args(["mcp", "--cache"]).arg(&cache);
+    let responses = session_of(
+        command,
+        &[call(1, serde_json::json!({ "query": "deployment", "budget": 4096 }))],
+    );
+    let text = responses[0]["result"]["content"][0]["text"].as_str().unwrap();
+
+    let cli = context_bin()
+        .arg("--config")
+        .arg(&config)
+        .args(["resolve", "--cache"])
+        .arg(&cache)
+        .args(["--query", "deployment", "--budget", "4096"])
+        .output()
+        .unwrap();
+    assert_eq!(format!("{text}\n").as_bytes(), cli.stdout);
+    let result: serde_json::Value = serde_json::from_str(text).unwrap();
+    assert_eq!(result["selection"]["pinned"]["ids"], serde_json::json!([pinned_id]));
+}
@@ -0,0 +1,112 @@
+//! Prompt-ready output format tests.
+//!
+//! Validates the markdown, text, and xml renderings of `context resolve`
+//! and the `--template` language.
+
+use std::fs;
+
+mod common;
+
+use common::context_bin;
+
+const USAGE_ERROR: i32 = 1;
+
+/// Build a one-document cache whose content needs XML escaping.
+fn build_cache(tmp: &std::path::Path) -> std::path::PathBuf {
+    common::build_cache(
+        tmp,
+        "cache",
+        &[("deployment.md", "Deployment guide: run <deploy> & verify")],
+    )
+}
+
+fn resolve(cache: &std::path::Path, extra: &[&str]) -> std::process::Output {
+    context_bin()
+        .args(["resolve", "--cache"])
+        .arg(cache)
+        .args(["--query", "deployment", "--budget", "4096"])
+        .args(extra)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn markdown_and_text_render_headers_and_content() {
+    let tmp = tempfile::tempdir().unwrap();
+    let cache = build_cache(tmp.path());
+
+    let markdown = resolve(&cache, &["--format", "markdown"]);
+    assert!(markdown.status.success());
+    let markdown = String::from_utf8(markdown.stdout).unwrap();
+    assert!(markdown.starts_with("## Document 1\n"), "got: {markdown}");
+    assert!(markdown.contains("- **source**: deployment.md\n"));
+    assert!(markdown.contains("Deployment guide: run <deploy> & verify\n"));
+
+    let text = resolve(&cache, &["--format", "text", "--header", "source"]);
+    assert!(text.status.success());
+    assert_eq!(
+        String::from_utf8(text.stdout).unwrap(),
+        "source: deployment.md\n\nDeployment guide: run <deploy> & verify\n"
+    );
+}
+
+#[test]
+fn xml_escapes_content_and_attributes() {
+    let tmp = tempfile::tempdir().unwrap();
+    let cache = build_cache(tmp.path());
+
+    let output = resolve(&cache, &["--format", "xml", "--header", "source"]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "<context query=\"deployment\" budget=\"4096\">\n\
+         <document source=\"deployment.md\">\n\
+         Deployment guide: run &lt;deploy&gt; &amp; verify\n\
+         </document>\n\
+         </context>\n"
+    );
+}
+
+#[test]
+fn template_renders_documents_deterministically() {
+    let tmp = tempfile::tempdir().unwrap();
+    let cache = build_cache(tmp.path());
+
+    let template = tmp.path().join("prompt.tmpl");
+    fs::write(
+        &template,
+        "Q: {{query}} ({{document_count}} docs)\n\
+         {{#documents}}[{{rank}}] {{source}}\n{{content}}\n{{/documents}}",
+    )
+    .unwrap();
+
+    let first = resolve(&cache, &["--template", template.to_str().unwrap()]);
+    let second = resolve(&cache, &["--template", template.to_str().unwrap()]);
+    assert!(
+        first.status.success(),
+        "template render failed: {}",
+        String::from_utf8_lossy(&first.stderr)
+    );
+    assert_eq!(first.stdout, second.stdout);
+    assert_eq!(
+        String::from_utf8(first.stdout).unwrap(),
+        "Q: deployment (1 docs)\n[1] deployment.md\nDeployment guide: run <deploy> & verify\n"
+    );
+}
+
+#[test]
+fn template_with_unknown_variable_is_usage_error() {
+    let tmp = tempfile::tempdir().unwrap();
+    let cache = build_cache(tmp.path());
+
+    let template = tmp.path().join("bad.tmpl");
+    fs::write(&template, "{{content}}").unwrap();
+
+    let output = resolve(&cache, &["--template", template.to_str().unwrap()]);
+    assert_eq!(
+        output.status.code().unwrap(),
+        USAGE_ERROR,
+        "{{{{content}}}} outside {{{{#documents}}}} should return exit code {}",
+        USAGE_ERROR
+    );
+}
@@ -0,0 +1,164 @@
+//! HTTP server tests.
+//!
+//! Runs `context serve` on a loopback port and validates that its responses
+//! are byte-identical to the CLI and that errors carry the frozen codes.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::process::{Child, Stdio};
+
+mod common;
+
+use common::{context_bin, two_doc_cache};
+
+/// A running server, killed when dropped.
+struct Server {
+    child: Child,
+    addr: String,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Start a server on a free loopback port and wait until it is listening.
+fn serve(cache: &std::path::Path) -> Server {
+    serve_with(cache, &[])
+}
+
+fn serve_with(cache: &std::path::Path, extra: &[&str]) -> Server {
+    let mut child = context_bin()
+        .args(["serve", "--listen", "127.0.0.1:0", "--cache"])
+        .arg(cache)
+        .args(extra)
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut line = String::new();
+    BufReader::new(child.stderr.take().unwrap())
+        .read_line(&mut line)
+        .unwrap();
+    let addr = line
+        .trim()
+        .strip_prefix("Listening on http://")
+        .unwrap_or_else(|| panic!("unexpected startup line: {line:?}"))
+        .to_string();
+    Server { child, addr }
+}
+
+/// Send one request and return the status code and body.
+fn request(server: &Server, method: &str, path: &str, body: &str) -> (u16, Vec<u8>) {
+    let mut stream = TcpStream::connect(&server.addr).unwrap();
+    write!(
+        stream,
+        "{method} {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\
+         Content-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+        body.len()
+    )
+    .unwrap();
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).unwrap();
+    let split = response
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .expect("response has no header terminator");
+    let head = String::from_utf8_lossy(&response[..split]);
+    let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+    (status, response[split + 4..].to_vec())
+}
+
+#[test]
+fn serve_matches_cli_output_bytes() {
+    let tmp = tempfile::tempdir().unwrap();
+    let cache = two_doc_cache(tmp.path());
+    let server = serve(&cache);
+
+    for format in ["json", "pretty"] {
+        let cli = context_bin()
+            .args(["resolve", "--cache"])
+            .arg(&cache)
+            .args(["--query", "deployment", "--budget", "4096", "--format", format])
+            .output()
+            .unwrap();
+        let body =
+            format!("{{\"query\": \"deployment\", \"budget\": 4096, \"format\": \"{format}\"}}");
+        let (status, served) = request(&server, "POST", "/resolve", &body);
+        assert_eq!(status, 200);
+        assert_eq!(served, cli.stdout, "POST /resolve must match `resolve --format {format}`");
+    }
+
+    let cli = context_bin()
+        .args(["inspect", "--cache"])
+        .arg(&cache)
+        .output()
+        .unwrap();
+    let (status, served) = request(&server, "GET", "/inspect", "");
+    assert_eq!(status, 200);
+    assert_eq!(served, cli.stdout, "GET /inspect must match `inspect`");
+}
+
+#[test]
+fn serve_errors_carry_frozen_codes() {
+    let tmp = tempfile::tempdir().unwrap();
+    let cache = two_doc_cache(tmp.path());
+    let server = serve(&cache);
+
+    let cases = [
+        ("POST", "/resolve", "{\"query\": \"bad\\u0007\", \"budget\": 10}", 400, 2),
+        ("POST", "/resolve", "{\"query\": \"deployment\"}", 400, 3),
+        ("POST", "/resolve", "not json", 400, 1),
+        ("GET", "/resolve", "", 405, 1),
+        ("GET", "/missing", "", 404, 1),
+    ];
+    for (method, path, body, expected_status, exit_code) in cases {
+        let (status, served) = request(&server, method, path, body);
+        assert_eq!(status, expected_status, "{method} {path} {body}");
+        let error: serde_json::Value = serde_json::from_slice(&served).unwrap();
+        assert_eq!(error["error"]["exit_code"], exit_code, "{method} {path} {body}");
+    }
+
+    // The server keeps serving after errors
+    let (status, _) = request(&server, "GET", "/inspect", "");
+    assert_eq!(status, 200);
+}
+
+#[test]
+fn serve_applies_candidate_options() {
+    let tmp = tempfile::tempdir().unwrap();
+    let cache = two_doc_cache(tmp.path());
+    let options = ["--path-prefix", "api"];
+    let server = serve_with(&cache, &options);
+
+    let cli = context_bin()
+        .args(["resolve", "--cache"])
+        .arg(&cache)
+        .args(["--query", "deployment", "--budget", "4096"])
+        .args(options)
+        .output()
+        .unwrap();
+    let (status, served) = request(
+        &server,
+        "POST",
+        "/resolve",
+        r#"{"query": "deployment", "budget": 4096}"#,
+    );
+    assert_eq!(status, 200);
+    assert_eq!(served, cli.stdout);
+    let result: serde_json::Value = serde_json::from_slice(&served).unwrap();
+    assert_eq!(result["selection"]["filters"]["path_prefixes"], serde_json::json!(["api"]));
+
+    // Filters shape selection, not the cache /inspect describes
+    let cli = context_bin()
+        .args(["inspect", "--cache"])
+        .arg(&cache)
+        .output()
+        .unwrap();
+    let (status, served) = request(&server, "GET", "/inspect", "");
+    assert_eq!(status, 200);
+    assert_eq!(served, cli.stdout, "GET /inspect must match `inspect` under filters");
+}